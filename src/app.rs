@@ -1,7 +1,7 @@
 pub mod commands;
 
-use std::{env, error::Error, process};
-use commands::{Runnable, parse_command};
+use std::{env, error::Error, io, process};
+use commands::{NoMatch, Runnable, parse_command, split_options};
 use crate::helpers::input::parse_command_option;
 
 
@@ -36,13 +36,14 @@ impl App {
     /// let app = App::from_args(&cmd).unwrap();
     /// ```
     pub fn from_args(args: &Vec<String>) -> Result<App, Box<dyn Error>> {
-        let options = parse_command_option(args);
+        let (args, options_end) = split_options(args);
+        let options = parse_command_option(&args[..options_end]);
         if options.contains_key("version") {
             println!("{APP_VERSION}");
             process::exit(0);
         }
         Ok(App {
-            action: parse_command(args, options)?,
+            action: parse_command(&args, options)?,
         })
     }
 
@@ -60,12 +61,33 @@ impl App {
         self.action.run()?;
         Ok(())
     }
+
+    /// Exit code of an error of run: 1 when the command found nothing (search), the error code of the command otherwise
+    pub fn exit_code(&self, err: &(dyn Error + 'static)) -> i32 {
+        if Self::is_no_match(err) { 1 } else { self.action.error_code() }
+    }
+
+    /// The command ran without error but found nothing, nothing to display
+    pub fn is_no_match(err: &(dyn Error + 'static)) -> bool {
+        err
+            .downcast_ref::<io::Error>()
+            .and_then(|err| err.get_ref())
+            .is_some_and(|err| err.is::<NoMatch>())
+    }
+
+    /// Exit code of an error of from_env (invalid arguments): 2 for search, 1 for the other commands
+    pub fn args_exit_code() -> i32 {
+        match env::args().nth(1).as_deref() {
+            Some("search") => 2,
+            _ => 1,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::{io:: {Error, ErrorKind}, cell::RefCell, rc::Rc};
-    use super::{App, commands::Runnable};
+    use super::{App, commands::{NoMatch, Runnable}};
 
     #[test]
     fn from_args() {
@@ -116,4 +138,31 @@ mod tests {
             Err(err) => assert_eq!(err.to_string(), "WouldBlock", "App run should fail with message 'WouldBlock'"),
         };
     }
+
+    #[test]
+    fn exit_code() {
+        struct MockRunnable {
+            no_match: bool,
+            search: bool,
+        }
+        impl Runnable for MockRunnable {
+            fn run(&self) -> Result<(), Error> {
+                match self.no_match {
+                    true => Err(Error::other(NoMatch)),
+                    false => Err(Error::new(ErrorKind::NotFound, "read error")),
+                }
+            }
+            fn error_code(&self) -> i32 {
+                if self.search { 2 } else { 1 }
+            }
+        }
+
+        for (no_match, search, code) in [(true, true, 1), (false, true, 2), (false, false, 1)] {
+            let app = App {
+                action: Box::new(MockRunnable { no_match, search })
+            };
+            let err = app.run().unwrap_err();
+            assert_eq!(code, app.exit_code(err.as_ref()));
+        }
+    }
 }
@@ -11,8 +11,10 @@ pub mod info;
 pub mod mserv;
 pub mod transcode;
 pub mod renamemovie;
+pub mod index;
+pub mod replace;
 
-use std::{io::{Error, ErrorKind}, collections::HashMap};
+use std::{io::{Error, ErrorKind}, collections::HashMap, fmt};
 
 type Result<T> = std::result::Result<T, std::io::Error>;
 
@@ -21,8 +23,25 @@ const OPTION_SEPARATOR: char = ',';
 /// All commands must implement this Trait
 pub trait Runnable {
     fn run(&self) -> Result<()>;
+
+    /// Exit code when run fails: 1, search uses 2 to tell errors from no match
+    fn error_code(&self) -> i32 {
+        1
+    }
+}
+
+/// Error of a command that ran without error but found nothing (search), exit code 1
+#[derive(Debug)]
+pub struct NoMatch;
+
+impl fmt::Display for NoMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no match")
+    }
 }
 
+impl std::error::Error for NoMatch {}
+
 /// Parse the comand line
 /// 
 /// # Arguments
@@ -59,6 +78,8 @@ pub fn parse_command(args: &Vec<String>, options: HashMap<String, String>) -> Re
         "mserv" => Ok(Box::new(mserv::build_cmd(args, options)?)),
         "transcode" => Ok(Box::new(transcode::build_cmd(args, options)?)),
         "renamemovie" => Ok(Box::new(renamemovie::build_cmd(args, options)?)),
+        "index" => Ok(Box::new(index::build_cmd(args, options)?)),
+        "replace" => Ok(Box::new(replace::build_cmd(args, options)?)),
         _ => Err(Error::new(
             ErrorKind::InvalidInput, 
             format!("'{cmd}' is not a valid command{}", help::help_command())
@@ -66,6 +87,34 @@ pub fn parse_command(args: &Vec<String>, options: HashMap<String, String>) -> Re
     }
 }
 
+/// Arguments without the `--` end-of-options marker, and the number of the first ones that may hold options:
+/// the ones before `--`, otherwise all but the search query and the replace pattern and replacement,
+/// read as they are even if they start with '-' (`oms search /home/me/notes "-draft report"`)
+///
+/// # Examples
+///
+/// ```
+/// use oms::app::commands::split_options;
+/// let args: Vec<String> = ["oms", "search", "-i", "/home/me", "-draft"].iter().map(|arg| arg.to_string()).collect();
+/// assert_eq!(4, split_options(&args).1);
+///
+/// let args: Vec<String> = ["oms", "info", "--", "-file.mp4"].iter().map(|arg| arg.to_string()).collect();
+/// assert_eq!((vec!["oms".to_string(), "info".to_string(), "-file.mp4".to_string()], 2), split_options(&args));
+/// ```
+pub fn split_options(args: &[String]) -> (Vec<String>, usize) {
+    if let Some(end) = args.iter().position(|arg| arg == "--") {
+        let mut args = args.to_vec();
+        args.remove(end);
+        return (args, end);
+    }
+    let positional = match args.get(1).map(String::as_str) {
+        Some("search") if args.len() >= 4 => 1,
+        Some("replace") if args.len() >= 5 => 2,
+        _ => 0,
+    };
+    (args.to_vec(), args.len() - positional)
+}
+
 /// Get item of a string vector a the givent index
 /// 
 /// # Arguments
@@ -100,6 +149,22 @@ pub fn get_args_parameter<'a>(args: &'a Vec<String>, index:usize, error_message:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::input::parse_command_option;
+
+    #[test]
+    fn split_options_excluded_term() {
+        let args: Vec<String> = ["oms", "search", "--hidden", "/tmp/t", "-bar foo"].iter().map(|arg| arg.to_string()).collect();
+        let (args, options_end) = split_options(&args);
+        let options = parse_command_option(&args[..options_end]);
+        assert_eq!(vec!["hidden"], options.keys().collect::<Vec<_>>());
+        assert!(parse_command(&args, options).is_ok());
+        assert_eq!(["/tmp/t", "-bar foo"], args[args.len() - 2..]);
+
+        let args: Vec<String> = ["oms", "replace", "--", "/tmp/t", "-old", "-new"].iter().map(|arg| arg.to_string()).collect();
+        let (args, options_end) = split_options(&args);
+        assert!(parse_command_option(&args[..options_end]).is_empty());
+        assert_eq!(["/tmp/t", "-old", "-new"], args[args.len() - 3..]);
+    }
 
     #[test]
     fn parse_args_info_single_parameter() {
@@ -1,5 +1,5 @@
 use std::{io, collections::HashMap};
-use super::{Runnable, info, read, search, mserv, transcode, renamemovie};
+use super::{Runnable, info, read, search, mserv, transcode, renamemovie, index, replace};
 
 
 /// # Help command
@@ -16,6 +16,7 @@ impl Runnable for Help {
     fn run(&self) -> Result<(), io::Error> {
         println!("\
 \nUsage:  oms <command> [options] <args>
+        oms <command> [options] -- <args>   arguments after -- are never read as options
 
 Available commands:
 
@@ -26,6 +27,8 @@ Available commands:
 {}
 {}
 {}
+{}
+{}
 ",
         self::usage(),
         info::usage(),
@@ -34,6 +37,8 @@ Available commands:
         mserv::usage(),
         transcode::usage(),
         renamemovie::usage(),
+        index::usage(),
+        replace::usage(),
 );
         Ok(())
     }
@@ -0,0 +1,213 @@
+use std::{collections::{BTreeSet, HashMap}, fs, io::{Error, ErrorKind}, sync::mpsc::{self, Sender}, time::UNIX_EPOCH};
+use crate::helpers::{file::{self, archive::archive_format, magic, walk::walk}, threadpool::ThreadPool};
+use super::{get_args_parameter, Runnable, OPTION_SEPARATOR};
+use super::search::{archive::ArchiveSearch, document::Document, file_document, index::SearchIndex, is_excluded, is_searchable, option::SearchOption};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// # Index command
+///
+/// Extract the text of the files with the search handlers (pdf, epub, office, subtitles, ocr...)
+/// and store it in an inverted index in the cache path, then `search --index` answers from it
+///
+/// ## Usage
+///
+/// `cargo run -- index --cache-path="/media/solofo/MEDIA/.oms" /media/solofo/MEDIA/books`
+/// `cargo run -- search --index --cache-path="/media/solofo/MEDIA/.oms" /media/solofo/MEDIA/books nautilus`
+///
+/// Only the files whose modification time or size changed are extracted again
+pub struct Index {
+    /// path of the file or directory to index
+    file_path: String,
+    /// Command options
+    cmd_options: HashMap<String, String>,
+}
+
+/// Documents extracted from a file, with its modification time and size
+struct IndexedFile {
+    file_path: String,
+    modification_time: u64,
+    size: u64,
+    documents: Vec<Document>,
+}
+
+impl Runnable for Index {
+    fn run(&self) -> Result<()> {
+        // Same handlers and filters as the search command, without query
+        let mut search_option = SearchOption::new(String::new());
+
+        // --help
+        if self.cmd_options.contains_key("h") || self.cmd_options.contains_key("help") {
+            println!("\n{}\n", usage());
+            return Ok(());
+        }
+
+        for (option, value) in &self.cmd_options {
+            match option.as_str() {
+                "p" | "provider" => search_option.set_provider(value)?,
+                "t" | "thread" => search_option.set_thread(value)?,
+                "binary" => search_option.set_binary(value)?,
+                "archive-depth" => search_option.set_archive_depth(value)?,
+                "max-depth" => search_option.walk.set_max_depth(value)?,
+                "hidden" => search_option.walk.set_hidden(),
+                "follow-symlinks" => search_option.walk.set_follow_symlinks(),
+                "e" | "extensions" => search_option.extensions_from(value)?,
+                "exclude-extensions" => search_option.exclude_extensions_from(value)?,
+                "f" | "files" => search_option.files_from(value)?,
+                "exclude-files" => search_option.exclude_files_from(value)?,
+                arg => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("\nUnkown argument {}\n", arg)
+                    ));
+                },
+            };
+        }
+
+        let mut index = SearchIndex::load()?;
+        // Paths are indexed absolute, to search from any directory
+        let path = match fs::canonicalize(&self.file_path) {
+            Ok(path) => path.display().to_string(),
+            Err(err) => return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("\n{}\nread error: {}\n\n", self.file_path, err)
+            )),
+        };
+
+        search_option.base_path = path.clone();
+        let thread_pool = ThreadPool::new(search_option.thread);
+        let (tx, rx) = mpsc::channel();
+        let mut found = BTreeSet::new();
+        let mut unchanged = 0;
+
+        walk(&path, &search_option.walk, |file_path| {
+            // Type of the file detected once, from its content
+            let extension = magic::media_extension(&file_path);
+            let archive = archive_format(&file_path, &extension).filter(|_| search_option.archive_depth > 0);
+            if !is_indexable(&file_path, archive.is_some(), &search_option) {
+                return;
+            }
+            found.insert(file_path.clone());
+            let modification_time = modification_time(&file_path);
+            let size = file::file_size(&file_path).unwrap_or_default();
+            if index.is_up_to_date(&file_path, modification_time, size) {
+                unchanged += 1;
+                return;
+            }
+            let search_option = search_option.clone();
+            let tx = tx.clone();
+            thread_pool.execute(move || extract_file(file_path, &extension, archive, modification_time, size, &search_option, tx));
+        });
+        drop(tx);
+
+        let mut updated = 0;
+        for indexed_file in rx {
+            index.insert(&indexed_file.file_path, indexed_file.modification_time, indexed_file.size, indexed_file.documents)?;
+            updated += 1;
+        }
+
+        // Deleted files, or files not matching the filters anymore
+        let mut removed = 0;
+        for file_path in index.files_in(&path) {
+            if !found.contains(&file_path) {
+                index.remove(&file_path);
+                removed += 1;
+            }
+        }
+        index.save()?;
+
+        println!("{} files indexed: {updated} updated, {unchanged} unchanged, {removed} removed", found.len());
+        Ok(())
+    }
+}
+
+/// Same filters as the search command: archives are opened, their files are filtered
+fn is_indexable(file_path: &str, is_archive: bool, search_option: &SearchOption) -> bool {
+    if is_archive {
+        return !is_excluded(file_path, search_option);
+    }
+    is_searchable(file_path, search_option)
+}
+
+/// Nanoseconds since epoch, changes within the same second are detected
+fn modification_time(file_path: &str) -> u64 {
+    fs::metadata(file_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_nanos() as u64)
+}
+
+/// `extension` is the detected type of the file, `archive` its archive format if it is opened
+fn extract_file(file_path: String, extension: &str, archive: Option<&str>, modification_time: u64, size: u64, search_option: &SearchOption, tx: Sender<IndexedFile>) {
+    let mut documents = vec![];
+    if let Some(format) = archive {
+        ArchiveSearch {
+            file_path: &file_path,
+            display_path: &file_path,
+            format,
+            search_option,
+            depth: 1,
+        }.documents(&mut |document| documents.push(document));
+    } else {
+        documents.extend(file_document(&file_path, extension, search_option));
+    }
+    tx.send(IndexedFile {
+        file_path,
+        modification_time,
+        size,
+        documents,
+    }).unwrap_or_default();
+}
+
+/// Help message for this command
+pub fn usage() -> String {
+    format!("\
+index [options] <file_path|directory_path>
+    Extract the text of the files (as the search command does) and store it in an index in the cache path,
+    then 'search --index' searches in the index without reading the files.
+    Only new and modified files (modification time or size) are extracted again, deleted files are removed.
+
+    --help
+    --cache-path=<string>   Cache path, where the index is stored (required)
+    -p <string> --provider=<string>   possible value: local (default), api, use for external information provider
+    -e <string> --extensions=<string>    Index only these file extensions, separated by '{OPTION_SEPARATOR}'
+    --exclude-extensions=<string>    exlude these file extensions, separated by '{OPTION_SEPARATOR}'
+    -f <> --files=<string>  Index only these files: names, globs (*.min.js, **/build/**) or regex (re:...), separated by '{OPTION_SEPARATOR}'
+    --exclude-files=<string>    exlude these files (names, globs or regex), separated by '{OPTION_SEPARATOR}'
+    --binary=<string>   skip (default), name-only (index only the name), text (index binary files as text)
+    -t <int> --thread=<int>    Max thread number
+    --max-depth=<int>   Max depth of sub directories, 1 for the files of the given directory only
+    --hidden    Index also hidden files and directories
+    --follow-symlinks   Follow symbolic links to directories
+    --archive-depth=<int>   Index the files of zip, tar, tar.gz, tgz files, and archives inside up to this depth, default 3
+")
+}
+
+/// Returns Index from command line args
+///
+/// # Arguments
+///
+/// * `args` - A Vector string from command line
+///
+/// # Examples
+///
+/// ```
+/// use oms::app::commands::index;
+/// use std::collections::HashMap;
+///
+/// let args = vec!["oms".to_string(), "index".to_string(), "/home/me/books".to_string()];
+/// index::build_cmd(&args, HashMap::new());
+/// ```
+pub fn build_cmd(args: &Vec<String>, options: HashMap<String, String>) -> Result<Index> {
+    let file_path = get_args_parameter(
+        args,
+        args.len() - 1, // Get last agruments
+        "\nindex error: 'file_path' parameter required\n"
+    ).unwrap_or_default();
+
+    Ok(Index {
+        file_path: file_path.to_string(),
+        cmd_options: options,
+    })
+}
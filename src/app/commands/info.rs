@@ -3,13 +3,16 @@ mod video;
 mod image;
 mod option;
 mod audio;
+mod office;
+mod format;
 
-use std::{io::{self, Error, ErrorKind}, collections::HashMap, fs, path::Path, sync::{mpsc::{self, Sender}, Arc, Mutex}};
+use std::{io::{self, Error, ErrorKind}, collections::HashMap, fs, sync::{mpsc::{self, Receiver, Sender}, Arc, Mutex}};
 use once_cell::sync::Lazy;
+use colored::Colorize;
 
-use crate::helpers::{file::{get_extension, self}, threadpool::ThreadPool};
+use crate::helpers::{file::{self, pattern::{glob_files, is_pattern}, walk::walk}, threadpool::ThreadPool};
 use super::{Runnable, get_args_parameter};
-use self::{pdf::PdfInfo, video::VideoInfo, option::InfoOption, image::ImageInfo, audio::AudioInfo};
+use self::{pdf::PdfInfo, video::VideoInfo, option::InfoOption, image::ImageInfo, audio::AudioInfo, office::OfficeInfo};
 
 
 /// # Info command
@@ -71,7 +74,18 @@ impl Runnable for Info {
                 "hide-preview" => info_option.hide_preview(),
                 "elastic-url" => info_option.set_elastic(value)?,
                 "t" | "thread" => info_option.set_thread(value)?,
+                "max-depth" => info_option.walk.set_max_depth(value)?,
+                "hidden" => info_option.walk.set_hidden(),
+                "follow-symlinks" => info_option.walk.set_follow_symlinks(),
+                "min-size" => info_option.filter.set_min_size(value)?,
+                "max-size" => info_option.filter.set_max_size(value)?,
+                "newer-than" => info_option.filter.set_newer_than(value)?,
+                "older-than" => info_option.filter.set_older_than(value)?,
+                "min-duration" => info_option.filter.set_min_duration(value)?,
+                "codec" => info_option.filter.set_codec(value)?,
+                "resolution" => info_option.filter.set_resolution(value)?,
                 "u" | "update-metadata" => info_option.set_update_metadata(),
+                "format" => info_option.set_format(value)?,
                 "list" => {
                     info_option.set_list(value)?; // Files are provided in option
                     file_path.clear(); // Ignore the file in last option
@@ -109,10 +123,8 @@ impl Runnable for Info {
         };
 
         drop(tx);
-        for message in rx {
-            if info_option.display_preview == true {
-                println!("{message}");
-            }
+        if info_option.display_preview {
+            print_messages(rx, &info_option.format);
         }
 
         *b_isrunning = false;
@@ -120,48 +132,77 @@ impl Runnable for Info {
     }
 }
 
-fn dir_info(dir_path: &String, info_option: &InfoOption, thread_pool: &ThreadPool, tx: Sender<String>) {
-    for entry in fs::read_dir(Path::new(&dir_path)).unwrap() {
-        let path = entry.unwrap().path();
-        if path.is_file() {
-            file_info(&path.to_str().unwrap().to_string(), &info_option, thread_pool, tx.clone())
-        } else if path.is_dir() {
-            dir_info(&path.to_str().unwrap().to_string(), &info_option, thread_pool, tx.clone())
-        }
+/// Results as they come: previews (text), an array (json), one object by line (ndjson) or rows after the header (csv)
+fn print_messages(rx: Receiver<String>, format: &str) {
+    match format {
+        "json" => {
+            let mut separator = "[\n";
+            for message in rx {
+                print!("{separator}{message}");
+                separator = ",\n";
+            }
+            println!("{}]", if separator == "[\n" { "[" } else { "\n" });
+        },
+        "csv" => {
+            println!("{}", format::csv_header());
+            rx.into_iter().for_each(|message| println!("{message}"));
+        },
+        _ => rx.into_iter().for_each(|message| println!("{message}")),
     }
 }
 
+fn dir_info(dir_path: &String, info_option: &InfoOption, thread_pool: &ThreadPool, tx: Sender<String>) {
+    walk(dir_path, &info_option.walk, |file_path| {
+        file_info(&file_path, info_option, thread_pool, tx.clone())
+    });
+}
+
 fn file_info(file_path: &String, info_option: &InfoOption, thread_pool: &ThreadPool, tx: Sender<String>)  {
     let file_path = file_path.clone();
     let info_option = info_option.clone();
     thread_pool.execute(move || {
-        if file::is_pdf_file(&file_path) {
+        if !info_option.filter.is_match(&file_path) {
+            return;
+        }
+        // Type of the file detected once, from its content
+        let extension = file::magic::media_extension(&file_path);
+        let is = |extensions: &[&str]| extensions.contains(&extension.as_str());
+        if is(&file::PDF_EXTENSIONS) {
             PdfInfo {
                 file_path: &file_path,
                 info_option: &info_option,
             }.info(tx);
         }
-        else if file::is_image_file(&file_path) {
+        else if is(&file::IMAGE_EXTENSIONS) {
             ImageInfo {
                 file_path: &file_path,
                 info_option: &info_option,
             }.info(tx);
         }
-        else if file::is_audio_file(&file_path) {
+        else if is(&file::AUDIO_EXTENSIONS) {
             AudioInfo { 
                 file_path: &file_path,
                 info_option: &info_option,
             }.info(tx);
         }
+        else if is(&file::MS_EXTENSIONS) {
+            OfficeInfo {
+                file_path: &file_path,
+                info_option: &info_option,
+            }.info(tx);
+        }
         else if file::is_video_ignored_file(&file_path) {
             ();
         }
-        else if file::is_video_file(&file_path) || get_extension(&file_path).is_empty() {
+        else if is(&file::VIDEO_EXTENSIONS) {
             VideoInfo { 
                 file_path: &file_path,
                 info_option: &info_option,
             }.info(tx);
         }
+        else if info_option.format != "text" {
+            eprintln!("{file_path}: Format not supported");
+        }
         else {
             print!("\n{file_path}: Format not supported\n");
         }
@@ -177,6 +218,11 @@ fn file_info_from_list(info_option: &InfoOption, thread_pool: &ThreadPool, tx: S
             Ok(md) if md.is_file() => {
                 file_info(file_path, info_option, thread_pool, tx.clone());
             },
+            // Glob or regex of the files: /media/films/**/*.mkv
+            Err(_) if is_pattern(file_path) => match glob_files(file_path, &info_option.walk) {
+                Ok(files) => files.iter().for_each(|file_path| file_info(file_path, info_option, thread_pool, tx.clone())),
+                Err(err) => eprintln!("{}", format!("Warning: {err}").yellow()),
+            },
             _ => (),
         };
     }
@@ -193,9 +239,19 @@ info [OPTIONS] <file_path/dir_path>
     --cache-path=<string>   Cache path
     --elastic-url=<string>  Elastic search server
     --hide-preview=<bool>   Mute display
-    --list=<sting>          Path of a file containing the list of files to parse
+    --list=<sting>          Path of a file containing the list of files to parse, one path or glob (/media/films/**/*.mkv, re:...) by line
     --base-path=<string>   Dir path of relative root
     -u --update-metadata    Update file metadata (for api provider)
+    --format=<string>       Output: text (default), json (array), ndjson (one object by line), csv,
+                            each result has a kind field: video, audio, pdf, image, office, errors go to stderr
+    --min-size=<size> --max-size=<size>   Only files of this size, in bytes or with a unit: 500K, 4G
+    --newer-than=<date> --older-than=<date>  Only files modified after/before a date (2024-01-31) or a duration ago: 12h, 7d, 2w, 1m, 1y
+    --min-duration=<duration>   Only media lasting at least: 90 (seconds), 45min, 1h30min (ffprobe)
+    --codec=<string>    Only media with a stream using one of these codecs, separated by ',': h264,hevc,aac (ffprobe)
+    --resolution=<string>   Only videos at least this size: 720p, 1920x1080 (ffprobe)
+    --max-depth=<int>       Max depth of sub directories, 1 for the files of the given directory only
+    --hidden                Include hidden files and directories
+    --follow-symlinks       Follow symbolic links to directories
     For videos: info --elastic-url=<string> --cache-path=<string> [dir_path]
 "
 }
@@ -1,8 +1,7 @@
 use std::sync::mpsc::Sender;
-use colored::Colorize;
 use crate::helpers::{media::audio::{get_audio_result, AudioResult}, db::elastic::Elastic};
 
-use super::option::InfoOption;
+use super::{option::InfoOption, format::{format_result, send_error}};
 
 ///
 /// cargo run -- info /home/solofo/Documents/books/
@@ -17,16 +16,10 @@ impl<'a> AudioInfo<'a> {
         match get_audio_result(&self.info_option.base_path, &self.file_path) {
             Ok(audio) => {
                 save_elastic(&audio, &self.info_option.elastic);
-                tx.send(format!("\
-\n------------------------------------------------------------------------
-{audio}\n")).unwrap_or_default();
+                tx.send(format_result("audio", &audio, &self.info_option.format)).unwrap_or_default();
             },
             Err(err) => {
-                if self.info_option.display_preview == false {
-                    println!("\n{}\n", err.to_string().on_red());
-                } else {
-                    return tx.send(format!("\n{}\n", err.to_string().on_red())).unwrap_or_default();
-                }                
+                send_error(&tx, self.info_option, &err.to_string());
             }
         }
     }
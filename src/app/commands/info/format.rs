@@ -0,0 +1,106 @@
+use std::{fmt::Display, sync::mpsc::Sender};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use super::option::InfoOption;
+
+/// Columns of the csv format, the same for every kind, empty when the kind has no such field
+pub const CSV_COLUMNS: [&str; 16] = [
+    "kind", "title", "summary", "year", "genres", "casts", "album", "track", "rating", "provider",
+    "file_type", "file_path", "hash", "modification_time", "duration", "file_size",
+];
+
+/// Result exported by the json, ndjson and csv formats: its fields after the kind (video, audio, pdf, image, office)
+#[derive(Serialize)]
+struct InfoRecord<'a, T: Serialize> {
+    kind: &'a str,
+    #[serde(flatten)]
+    result: &'a T,
+}
+
+/// Message of a result: its preview (text), a json object on one line (json, ndjson) or a csv row
+pub fn format_result<T: Serialize + Display>(kind: &str, result: &T, format: &str) -> String {
+    let record = InfoRecord { kind, result };
+    match format {
+        "json" | "ndjson" => serde_json::to_string(&record).unwrap_or_default(),
+        "csv" => csv_row(&serde_json::to_value(&record).unwrap_or_default()),
+        _ => format!("\n------------------------------------------------------------------------\n{result}\n"),
+    }
+}
+
+/// Errors are displayed with the previews in text format, on stderr with the other formats to keep the output parsable
+pub fn send_error(tx: &Sender<String>, info_option: &InfoOption, message: &str) {
+    if info_option.format != "text" {
+        eprintln!("{message}");
+    } else if info_option.display_preview {
+        tx.send(format!("\n{}\n", message.on_red())).unwrap_or_default();
+    } else {
+        println!("\n{}\n", message.on_red());
+    }
+}
+
+pub fn csv_header() -> String {
+    CSV_COLUMNS.join(",")
+}
+
+/// Values of the csv columns, lists are joined with ", "
+fn csv_row(record: &Value) -> String {
+    CSV_COLUMNS
+        .iter()
+        .map(|column| match &record[*column] {
+            Value::Null => String::new(),
+            Value::String(text) => csv_field(text),
+            Value::Array(values) => csv_field(
+                &values.iter().map(|value| value.as_str().map_or(value.to_string(), String::from)).collect::<Vec<String>>().join(", ")
+            ),
+            value => csv_field(&value.to_string()),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Field quoted if it has a comma, a quote or a line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Book {
+        title: String,
+        genres: Vec<String>,
+        file_size: u64,
+    }
+
+    impl Display for Book {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Title: {}", self.title)
+        }
+    }
+
+    #[test]
+    fn format_info_result() {
+        let book = Book {
+            title: String::from("The \"Rust\" book, 2nd edition"),
+            genres: vec![String::from("Programming"), String::from("Reference")],
+            file_size: 1024,
+        };
+        assert_eq!(
+            r#"{"kind":"pdf","title":"The \"Rust\" book, 2nd edition","genres":["Programming","Reference"],"file_size":1024}"#,
+            format_result("pdf", &book, "json")
+        );
+        assert_eq!(
+            r#"pdf,"The ""Rust"" book, 2nd edition",,,"Programming, Reference",,,,,,,,,,,1024"#,
+            format_result("pdf", &book, "csv")
+        );
+        assert_eq!(CSV_COLUMNS.len(), csv_header().split(',').count());
+        assert!(format_result("pdf", &book, "text").ends_with("\nTitle: The \"Rust\" book, 2nd edition\n"));
+    }
+}
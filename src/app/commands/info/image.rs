@@ -1,8 +1,7 @@
 use std::sync::mpsc::Sender;
-use colored::Colorize;
 use crate::helpers::{media::image::{get_image_result, ImageResult}, db::elastic::Elastic};
 
-use super::option::InfoOption;
+use super::{option::InfoOption, format::{format_result, send_error}};
 
 pub struct ImageInfo<'a> {
     pub file_path: &'a String,
@@ -14,16 +13,10 @@ impl<'a> ImageInfo<'a> {
         match get_image_result(&self.info_option.base_path, &self.file_path) {
             Ok(image) => {
                 save_elastic(&image, &self.info_option.elastic);
-                tx.send(format!("\
-\n------------------------------------------------------------------------
-{image}\n")).unwrap_or_default();
+                tx.send(format_result("image", &image, &self.info_option.format)).unwrap_or_default();
             },
             Err(err) => {
-                if self.info_option.display_preview == false {
-                    println!("\n{}\n", err.to_string().on_red());
-                } else {
-                    return tx.send(format!("\n{}\n", err.to_string().on_red())).unwrap_or_default();
-                }                
+                send_error(&tx, self.info_option, &err.to_string());
             }
         }
     }
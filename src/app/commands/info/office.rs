@@ -0,0 +1,29 @@
+use std::sync::mpsc::Sender;
+use crate::helpers::{media::office::{get_office_result, OfficeResult}, db::elastic::Elastic};
+
+use super::{option::InfoOption, format::{format_result, send_error}};
+
+pub struct OfficeInfo<'a> {
+    pub file_path: &'a String,
+    pub info_option: &'a InfoOption,
+}
+
+impl<'a> OfficeInfo<'a> {
+    pub fn info(&self, tx: Sender<String>) {
+        match get_office_result(&self.info_option.base_path, self.file_path) {
+            Ok(office) => {
+                save_elastic(&office, &self.info_option.elastic);
+                tx.send(format_result("office", &office, &self.info_option.format)).unwrap_or_default();
+            },
+            Err(err) => {
+                send_error(&tx, self.info_option, &err.to_string());
+            }
+        }
+    }
+}
+
+fn save_elastic(office: &OfficeResult, elastic: &Option<Elastic>) {
+    if let Some(el) = elastic {
+        el.insert(&office.hash, &office);
+    }
+}
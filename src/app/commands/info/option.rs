@@ -1,5 +1,5 @@
 use std::{io::{Error, ErrorKind}, cmp::max, fs};
-use crate::helpers::{file, db::elastic::Elastic, rtrim_char};
+use crate::helpers::{file::{self, filter::FilterOption, walk::WalkOption}, db::elastic::Elastic, rtrim_char};
 
 type Result<T> = std::result::Result<T, std::io::Error>;
 
@@ -9,8 +9,11 @@ pub struct InfoOption {
     pub display_preview: bool,
     pub elastic: Option<Elastic>,
     pub thread: usize,
+    pub walk: WalkOption,
+    pub filter: FilterOption,
     pub provider: String,
     pub update_metadata: bool,
+    pub format: String,
 }
 
 impl InfoOption {
@@ -21,8 +24,11 @@ impl InfoOption {
             display_preview: true,
             elastic: None,
             thread: max(1, num_cpus::get() - 1),
+            walk: WalkOption::new(),
+            filter: FilterOption::new(),
             provider: String::from("api"),
             update_metadata: false,
+            format: String::from("text"),
         }
     }
 
@@ -90,6 +96,19 @@ impl InfoOption {
         self.update_metadata = true;
     }
 
+    pub fn set_format(&mut self, value: &str) -> Result<()> {
+        match value {
+            "text" | "json" | "ndjson" | "csv" => {
+                self.format = value.to_string();
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid format {value}, expected text, json, ndjson or csv")
+            ))
+        }
+    }
+
     pub fn set_thread(&mut self, value: &String) -> Result<()> {
         match value.parse::<usize>() {
             Ok(v) => {
@@ -112,8 +131,11 @@ impl Clone for InfoOption {
             display_preview: self.display_preview.clone(),
             elastic: self.elastic.clone(),
             thread: self.thread,
+            walk: self.walk.clone(),
+            filter: self.filter.clone(),
             provider: self.provider.clone(),
             update_metadata: self.update_metadata,
+            format: self.format.clone(),
         }
     }
 }
@@ -1,8 +1,7 @@
 use std::sync::mpsc::Sender;
-use colored::Colorize;
 use crate::helpers::{media::pdf::{get_pdf_result, result::PdfResult}, db::elastic::Elastic};
 
-use super::option::InfoOption;
+use super::{option::InfoOption, format::{format_result, send_error}};
 
 ///
 /// cargo run -- info /home/solofo/Documents/books/
@@ -13,20 +12,15 @@ pub struct PdfInfo<'a> {
 }
 
 impl<'a> PdfInfo<'a> {
+    /// The content is only read (OCR included) to be saved in the catalog
     pub fn info(&self, tx: Sender<String>) {
-        match get_pdf_result(&self.info_option.base_path, &self.file_path) {
+        match get_pdf_result(&self.info_option.base_path, self.file_path, self.info_option.elastic.is_some()) {
             Ok(pdf) => {
                 save_elastic(&pdf, &self.info_option.elastic);
-                tx.send(format!("\
-\n------------------------------------------------------------------------
-{pdf}\n")).unwrap_or_default();
+                tx.send(format_result("pdf", &pdf, &self.info_option.format)).unwrap_or_default();
             },
             Err(err) => {
-                if self.info_option.display_preview == false {
-                    println!("\n{}\n", err.to_string().on_red());
-                } else {
-                    return tx.send(format!("\n{}\n", err.to_string().on_red())).unwrap_or_default();
-                }                
+                send_error(&tx, self.info_option, &err.to_string());
             }
         }
     }
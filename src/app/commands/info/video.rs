@@ -2,7 +2,7 @@ use std::{sync::mpsc::Sender, time::SystemTime};
 use chrono::{DateTime, Utc};
 use colored::Colorize;
 use crate::helpers::{cache, db::elastic::Elastic, media::video::{self, result::get_video_result, metadata::VideoMetadata}};
-use super::option::InfoOption;
+use super::{option::InfoOption, format::{format_result, send_error}};
 
 /// 
 /// cargo run -- info /home/solofo/Videos
@@ -22,9 +22,7 @@ impl<'a> VideoInfo<'a> {
             Ok(mut videos) => {
                 save_elastic(&mut videos, &self.info_option.elastic);
                 for video in &videos {
-                    tx.send(format!("\
-\n------------------------------------------------------------------------
-{video}\n")).unwrap_or_default();
+                    tx.send(format_result("video", video, &self.info_option.format)).unwrap_or_default();
                 }
                 // Update file metadata if required
                 if self.info_option.update_metadata == true {
@@ -45,11 +43,7 @@ impl<'a> VideoInfo<'a> {
             },
             Err(err) => {
                 log_error(&self);
-                if self.info_option.display_preview == false {
-                    println!("\n{}\n", err.to_string().on_red());
-                } else {
-                    tx.send(format!("\n{}\n", err.to_string().on_red())).unwrap_or_default();
-                }
+                send_error(&tx, self.info_option, &err.to_string());
             }
         }
     }
@@ -1,4 +1,6 @@
 use std::{io::Error, thread::{self, JoinHandle}, collections::HashMap};
+use colored::Colorize;
+use crate::helpers::{file, media::pdf::get_pdf_pages};
 use super::{get_args_parameter, Runnable};
 use std::fs;
 
@@ -16,7 +18,7 @@ type Result<T> = std::result::Result<T, std::io::Error>;
 /// ## Features
 /// 
 /// * [x] Read text file: OK
-/// * [ ] Read pdf: TODO
+/// * [x] Read pdf: text of the pages, scanned pages by OCR
 /// * [ ] Read movie: TODO (?)
 /// * [ ] Read office file: TODO
 /// 
@@ -36,9 +38,13 @@ impl Runnable for Read {
             return Ok(());
         }
 
-        match read_text_file(&self.file_path).join() {
-            _ => Ok(()),
-        }
+        let handle = if file::is_pdf_file(&self.file_path) {
+            read_pdf_file(&self.file_path)
+        } else {
+            read_text_file(&self.file_path)
+        };
+        let _ = handle.join();
+        Ok(())
     }
 }
 
@@ -62,10 +68,22 @@ fn read_text_file(file_path: &String) -> JoinHandle<Result<()>> {
     })
 }
 
+fn read_pdf_file(file_path: &str) -> JoinHandle<Result<()>> {
+    let file_path = file_path.to_string();
+    thread::spawn(move || {
+        let hash = file::sha256(&file_path)?;
+        for (page, text) in get_pdf_pages(&file_path, &hash).iter().enumerate() {
+            println!("{}\n{text}\n", format!("Page {}", page + 1).dimmed());
+        }
+        Ok(())
+    })
+}
+
 /// Help message for this command
 pub fn usage() -> &'static str {
     "\
 read [OPTIONS] <file_path>        Display the content of the file
+    Text of the pages for a pdf, scanned pages are read by OCR (pdftoppm and tesseract)
 "
 }
 
@@ -1,6 +1,6 @@
-use std::{collections::HashMap, fs, io, path::Path};
+use std::{collections::HashMap, fs, io};
 use colored::Colorize;
-use crate::helpers::{media::video::{self, title::VideoTitle}, input, file};
+use crate::helpers::{media::video::{self, title::VideoTitle}, input, file::{self, walk::{walk, WalkOption}}};
 use super::{Runnable, get_args_parameter};
 
 type Result<T> = std::result::Result<T, std::io::Error>;
@@ -25,6 +25,9 @@ impl Runnable for RenameMovie {
         for (option, value) in &self.cmd_options {
             match option.as_str() {
                 "p" | "provider" => rename_option.set_provider(value)?,
+                "max-depth" => rename_option.walk.set_max_depth(value)?,
+                "hidden" => rename_option.walk.set_hidden(),
+                "follow-symlinks" => rename_option.walk.set_follow_symlinks(),
                 arg => return Err(io::Error::new(
                     io::ErrorKind::InvalidInput, 
                     format!("\nUnkown argument {}\n", arg)
@@ -48,6 +51,7 @@ impl RenameMovieOption {
     pub fn new() -> Self {
         RenameMovieOption {
             provider: String::from("local"),
+            walk: WalkOption::new(),
         }
     }
 
@@ -66,17 +70,11 @@ impl RenameMovieOption {
 }
 pub struct RenameMovieOption {
     pub provider: String,
+    pub walk: WalkOption,
 }
 
 fn rename_dir(dir_path: &String, rename_option: &RenameMovieOption) {
-    for entry in fs::read_dir(Path::new(&dir_path)).unwrap() {
-        let path = entry.unwrap().path();
-        if path.is_file() {
-            rename_file(&path.to_str().unwrap().to_string(), rename_option)
-        } else if path.is_dir() {
-            rename_dir(&path.to_str().unwrap().to_string(), rename_option)
-        }
-    }
+    walk(dir_path, &rename_option.walk, |file_path| rename_file(&file_path, rename_option));
 }
 
 fn get_title_year_from_provider(file_path: &String, file_name: &String) -> Result<(String, String)> {
@@ -177,6 +175,9 @@ Rename video file to \"Title (year)\"
 --help
 -p <string> --provider=<string>   allowed value: local (default), api
 --cache-path=<string>   Cache path
+--max-depth=<int>   Max depth of sub directories, 1 for the files of the given directory only
+--hidden    Include hidden files and directories
+--follow-symlinks   Follow symbolic links to directories
 "
 }
 
@@ -0,0 +1,274 @@
+pub mod option;
+pub mod diff;
+pub mod text;
+pub mod office;
+
+use std::{collections::HashMap, fs, io::{Error, ErrorKind}, path::Path, sync::mpsc::{self, Sender}};
+use colored::Colorize;
+use regex::Regex;
+use crate::helpers::{file::{self, archive::archive_format, encoding, magic, walk::walk}, threadpool::ThreadPool};
+use self::{diff::{unified_diff, Change}, office::{is_replaceable_office_file, replace_in_office}, option::ReplaceOption, text::replace_text};
+use super::{search::is_searchable, Runnable, OPTION_SEPARATOR};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Extension added to the original files with --backup
+const BACKUP_EXTENSION: &str = ".bak";
+
+/// # Replace command
+///
+/// Replace a pattern in text and office files (docx, xlsx, pptx),
+/// the files are selected as the search command does
+///
+/// ## Usage
+///
+/// `cargo run -- replace /home/me/deliverables "Acme (Corp|Inc)" "Globex $1"`
+/// `cargo run -- replace --apply --backup /home/me/deliverables "Acme (Corp|Inc)" "Globex $1"`
+///
+/// The diff of the changes is displayed, files are written only with --apply
+pub struct Replace {
+    /// path of the file or directory
+    file_path: String,
+    pattern: String,
+    replacement: String,
+    /// Command options
+    cmd_options: HashMap<String, String>,
+}
+
+/// Changes in a file, and its new content
+pub struct FileReplacement {
+    pub file_path: String,
+    /// The file itself for a text file, the xml parts of an office file
+    pub parts: Vec<PartReplacement>,
+    pub content: Vec<u8>,
+}
+
+/// Changes in a text, or in a part of an office file (report.docx!/word/document.xml)
+pub struct PartReplacement {
+    pub path: String,
+    /// Lines (paragraphs for office files) before replacement
+    pub lines: Vec<String>,
+    pub changes: Vec<Change>,
+    pub count: usize,
+}
+
+impl FileReplacement {
+    pub fn count(&self) -> usize {
+        self.parts.iter().map(|part| part.count).sum()
+    }
+}
+
+impl Runnable for Replace {
+    fn run(&self) -> Result<()> {
+        let mut replace_option = ReplaceOption::new(self.pattern.clone(), self.replacement.clone());
+
+        // --help
+        if self.cmd_options.contains_key("h") || self.cmd_options.contains_key("help") {
+            println!("\n{}\n", usage());
+            return Ok(());
+        }
+
+        for (option, value) in &self.cmd_options {
+            match option.as_str() {
+                "apply" => replace_option.set_apply(),
+                "backup" => replace_option.set_backup(),
+                "F" | "literal" => replace_option.set_literal(),
+                "i" | "ignore-case" => replace_option.set_ignore_case(),
+                "C" | "context" => replace_option.set_context(value)?,
+                "t" | "thread" => replace_option.search_option.set_thread(value)?,
+                "max-depth" => replace_option.search_option.walk.set_max_depth(value)?,
+                "hidden" => replace_option.search_option.walk.set_hidden(),
+                "follow-symlinks" => replace_option.search_option.walk.set_follow_symlinks(),
+                "min-size" => replace_option.search_option.filter.set_min_size(value)?,
+                "max-size" => replace_option.search_option.filter.set_max_size(value)?,
+                "newer-than" => replace_option.search_option.filter.set_newer_than(value)?,
+                "older-than" => replace_option.search_option.filter.set_older_than(value)?,
+                "e" | "extensions" => replace_option.search_option.extensions_from(value)?,
+                "exclude-extensions" => replace_option.search_option.exclude_extensions_from(value)?,
+                "f" | "files" => replace_option.search_option.files_from(value)?,
+                "exclude-files" => replace_option.search_option.exclude_files_from(value)?,
+                arg => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("\nUnkown argument {}\n", arg)
+                    ));
+                },
+            };
+        }
+        let regex = replace_option.regex()?;
+        replace_option.search_option.base_path = self.file_path.clone();
+
+        let thread_pool = ThreadPool::new(replace_option.search_option.thread);
+        let (tx, rx) = mpsc::channel();
+        match fs::metadata(&self.file_path) {
+            Ok(md) if md.is_file() => replace_in_file(&self.file_path, &regex, &replace_option, &thread_pool, tx.clone()),
+            Ok(md) if md.is_dir() => walk(&self.file_path, &replace_option.search_option.walk, |file_path| {
+                replace_in_file(&file_path, &regex, &replace_option, &thread_pool, tx.clone())
+            }),
+            Ok(_) => return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("\n{}\nreplace error: unknown file\n\n", self.file_path)
+            )),
+            Err(err) => return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("\n{}\nread error: {}\n\n", self.file_path, err)
+            )),
+        }
+
+        drop(tx);
+        let mut replacements: Vec<FileReplacement> = rx.into_iter().collect();
+        replacements.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        let mut count = 0;
+        let mut file_count = 0;
+        for replacement in replacements {
+            for part in &replacement.parts {
+                print!("{}", unified_diff(&part.path, &part.lines, &part.changes, replace_option.context));
+            }
+            if replace_option.apply {
+                if let Err(err) = write_file(&replacement.file_path, &replacement.content, replace_option.backup) {
+                    eprintln!("{}", format!("Warning: {}: {err}", replacement.file_path).yellow());
+                    continue;
+                }
+            }
+            count += replacement.count();
+            file_count += 1;
+        }
+
+        println!("\n{count} replacements in {file_count} files");
+        if !replace_option.apply && count > 0 {
+            println!("Nothing written, use --apply to write the changes");
+        }
+        Ok(())
+    }
+}
+
+/// Replace in a text or office file (docx, xlsx, pptx), other files are ignored
+fn replace_in_file(file_path: &String, regex: &Regex, replace_option: &ReplaceOption, thread_pool: &ThreadPool, tx: Sender<FileReplacement>) {
+    // Files inside archives are not written, backups of a previous run are kept as is
+    if !is_searchable(file_path, &replace_option.search_option) || file_path.ends_with(BACKUP_EXTENSION) {
+        return;
+    }
+    // Type of the file detected once, from its content
+    let extension = magic::media_extension(file_path);
+    let office = is_replaceable_office_file(&extension);
+    let media = [&file::PDF_EXTENSIONS[..], &file::EPUB_EXTENSIONS, &file::MS_EXTENSIONS, &file::VIDEO_EXTENSIONS, &file::AUDIO_EXTENSIONS, &file::IMAGE_EXTENSIONS];
+    if archive_format(file_path, &extension).is_some() || (!office && media.iter().any(|extensions| extensions.contains(&extension.as_str()))) {
+        return;
+    }
+
+    let file_path = file_path.clone();
+    let regex = regex.clone();
+    let replace_option = replace_option.clone();
+    thread_pool.execute(move || {
+        if !replace_option.search_option.filter.is_match(&file_path) {
+            return;
+        }
+        let replacement = if office {
+            replace_in_office(&file_path, &extension, &regex, &replace_option)
+        } else {
+            replace_in_text_file(&file_path, &regex, &replace_option)
+        };
+        match replacement {
+            Ok(Some(replacement)) => tx.send(replacement).unwrap_or_default(),
+            Ok(None) => (),
+            Err(err) => eprintln!("{}", format!("Warning: {file_path}: {err}").yellow()),
+        }
+    });
+}
+
+/// Binary files are skipped, the text is written back in its encoding (UTF-8 or UTF-16)
+fn replace_in_text_file(file_path: &str, regex: &Regex, replace_option: &ReplaceOption) -> Result<Option<FileReplacement>> {
+    let bytes = fs::read(file_path)?;
+    if encoding::is_binary(&bytes) {
+        return Ok(None);
+    }
+    let text = encoding::decode(&bytes)?;
+    let replacement = replace_text(&text, regex, replace_option);
+    if replacement.count == 0 {
+        return Ok(None);
+    }
+    Ok(Some(FileReplacement {
+        file_path: file_path.to_string(),
+        content: encoding::encode_as(&replacement.text, &bytes)?,
+        parts: vec![PartReplacement {
+            path: file_path.to_string(),
+            lines: text.lines().map(String::from).collect(),
+            changes: replacement.changes,
+            count: replacement.count,
+        }],
+    }))
+}
+
+/// Write the new content in a temporary file renamed over the file,
+/// the file is never partially written
+fn write_file(file_path: &str, content: &[u8], backup: bool) -> Result<()> {
+    let path = Path::new(file_path);
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.oms-replace"));
+    let written = fs::write(&temp_path, content)
+        .and_then(|_| fs::set_permissions(&temp_path, fs::metadata(path)?.permissions()))
+        .and_then(|_| if backup { fs::copy(path, format!("{file_path}{BACKUP_EXTENSION}")).map(|_| ()) } else { Ok(()) })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Help message for this command
+pub fn usage() -> String {
+    format!("\
+replace [options] <file_path|directory_path> <pattern> <replacement>
+    Replace a regular expression in text files and office files (docx, xlsx, pptx: text of the paragraphs).
+    Display the diff of the changes, files are written only with --apply.
+    Use $1, ${{name}} in the replacement for the capture groups, ^ and $ match at the start and end of lines.
+
+    --help
+    --apply     Write the changes (each file is replaced at once)
+    --backup    Keep the original files as <file>.bak (with --apply)
+    -F --literal    The pattern and the replacement are plain text
+    -i --ignore-case    Case insensitive pattern
+    -C <int> --context=<int>    Lines around the changes in the diff, default 3
+    -e <string> --extensions=<string>    Replace only in these file extensions, separated by '{OPTION_SEPARATOR}'
+    --exclude-extensions=<string>    exlude these file extensions, separated by '{OPTION_SEPARATOR}'
+    -f <> --files=<string>  Replace only in these files: names, globs (*.md, **/docs/**) or regex (re:...), separated by '{OPTION_SEPARATOR}'
+    --exclude-files=<string>    exlude these files (names, globs or regex), separated by '{OPTION_SEPARATOR}'
+    --min-size=<size> --max-size=<size>   Only files of this size, in bytes or with a unit: 500K, 4G
+    --newer-than=<date> --older-than=<date>  Only files modified after/before a date (2024-01-31) or a duration ago: 12h, 7d, 2w, 1m, 1y
+    -t <int> --thread=<int>    Max thread number
+    --max-depth=<int>   Max depth of sub directories, 1 for the files of the given directory only
+    --hidden    Replace also in hidden files and directories
+    --follow-symlinks   Follow symbolic links to directories
+")
+}
+
+/// Returns Replace from command line args
+///
+/// # Arguments
+///
+/// * `args` - A Vector string from command line
+///
+/// # Examples
+///
+/// ```
+/// use oms::app::commands::replace;
+/// use std::collections::HashMap;
+///
+/// let args = vec!["oms".to_string(), "replace".to_string(), "/home/me/docs".to_string(), "Acme".to_string(), "Globex".to_string()];
+/// replace::build_cmd(&args, HashMap::new());
+/// ```
+pub fn build_cmd(args: &[String], options: HashMap<String, String>) -> Result<Replace> {
+    if args.len() < 5 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "\nreplace error: <file_path> <pattern> <replacement> parameters required\n"
+        ));
+    }
+    Ok(Replace {
+        file_path: args[args.len() - 3].clone(),
+        pattern: args[args.len() - 2].clone(),
+        replacement: args[args.len() - 1].clone(),
+        cmd_options: options,
+    })
+}
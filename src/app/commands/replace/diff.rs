@@ -0,0 +1,97 @@
+use colored::Colorize;
+
+/// Lines replaced in a text
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    /// Index of the first replaced line, from 0
+    pub line: usize,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+/// Unified diff of the changes (sorted by line) made to lines, with context lines around
+pub fn unified_diff(path: &str, lines: &[String], changes: &[Change], context: usize) -> String {
+    let path = path.trim_start_matches('/');
+    let mut diff = format!("{}\n{}\n", format!("--- a/{path}").bold(), format!("+++ b/{path}").bold());
+    // Difference between the new and old line numbers before the current hunk
+    let mut offset: isize = 0;
+    let mut index = 0;
+    while index < changes.len() {
+        // Changes whose contexts overlap are in the same hunk
+        let mut last = index;
+        while last + 1 < changes.len()
+            && changes[last + 1].line <= changes[last].line + changes[last].old.len() + 2 * context {
+            last += 1;
+        }
+        let start = changes[index].line.saturating_sub(context);
+        let end = (changes[last].line + changes[last].old.len() + context).min(lines.len());
+
+        let mut body = String::new();
+        let mut line = start;
+        let mut new_count = 0;
+        for change in &changes[index..=last] {
+            for context_line in &lines[line..change.line] {
+                body.push_str(&format!(" {}\n", display(context_line)));
+            }
+            new_count += change.line - line + change.new.len();
+            for old in &change.old {
+                body.push_str(&format!("{}\n", format!("-{}", display(old)).red()));
+            }
+            for new in &change.new {
+                body.push_str(&format!("{}\n", format!("+{}", display(new)).green()));
+            }
+            line = change.line + change.old.len();
+        }
+        for context_line in &lines[line.min(end)..end] {
+            body.push_str(&format!(" {}\n", display(context_line)));
+        }
+        new_count += end.saturating_sub(line);
+
+        let new_start = (start as isize + offset).max(0) as usize;
+        diff.push_str(&format!("{}\n", format!("@@ -{},{} +{},{} @@", start + 1, end - start, new_start + 1, new_count).cyan()));
+        diff.push_str(&body);
+
+        offset += changes[index..=last].iter().map(|c| c.new.len() as isize - c.old.len() as isize).sum::<isize>();
+        index = last + 1;
+    }
+    diff
+}
+
+/// Line without its carriage return (CRLF files)
+fn display(line: &str) -> &str {
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diff_hunks() {
+        let lines: Vec<String> = (1..=10).map(|n| format!("line {n}")).collect();
+        let changes = vec![
+            Change { line: 1, old: vec!["line 2".to_string()], new: vec!["row 2".to_string()] },
+            Change { line: 3, old: vec!["line 4".to_string()], new: vec!["row 4".to_string(), "row 4b".to_string()] },
+            Change { line: 9, old: vec!["line 10".to_string()], new: vec!["row 10".to_string()] },
+        ];
+        // Without colors
+        let diff = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap().replace_all(&unified_diff("a.txt", &lines, &changes, 1), "").to_string();
+        assert_eq!("\
+--- a/a.txt
++++ b/a.txt
+@@ -1,5 +1,6 @@
+ line 1
+-line 2
++row 2
+ line 3
+-line 4
++row 4
++row 4b
+ line 5
+@@ -9,2 +10,2 @@
+ line 9
+-line 10
++row 10
+", diff);
+    }
+}
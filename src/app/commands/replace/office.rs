@@ -0,0 +1,251 @@
+use std::{fs::File, io::{self, BufReader, Cursor, Read, Write}};
+use quick_xml::{escape::partial_escape, events::Event, Reader};
+use regex::Regex;
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+use crate::helpers::file::archive::ARCHIVE_PATH_SEPARATOR;
+use super::{diff::Change, option::ReplaceOption, FileReplacement, PartReplacement};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Tag names of the paragraphs and of the text runs of an office format
+struct OfficeFormat {
+    paragraph: &'static [u8],
+    text: &'static [u8],
+    /// Xml parts containing the text
+    parts: fn(&str) -> bool,
+}
+
+/// A text element in the xml
+struct TextRun {
+    /// End of the start tag, where xml:space can be added
+    tag_end: usize,
+    preserve_space: bool,
+    /// Byte range of the escaped text
+    range: (usize, usize),
+    text: String,
+}
+
+/// docx, pptx and xlsx, `extension` is the type detected by magic::media_extension
+pub fn is_replaceable_office_file(extension: &str) -> bool {
+    office_format(extension).is_some()
+}
+
+fn office_format(extension: &str) -> Option<OfficeFormat> {
+    match extension {
+        "docx" => Some(OfficeFormat {
+            paragraph: b"w:p",
+            text: b"w:t",
+            parts: |name| name == "word/document.xml"
+                || ["header", "footer", "footnotes", "endnotes"].iter().any(|part| {
+                    name.starts_with(&format!("word/{part}")) && name.ends_with(".xml")
+                }),
+        }),
+        "pptx" => Some(OfficeFormat {
+            paragraph: b"a:p",
+            text: b"a:t",
+            parts: |name| (name.starts_with("ppt/slides/slide") || name.starts_with("ppt/notesSlides/notesSlide"))
+                && name.ends_with(".xml"),
+        }),
+        "xlsx" => Some(OfficeFormat {
+            paragraph: b"si",
+            text: b"t",
+            parts: |name| name == "xl/sharedStrings.xml",
+        }),
+        _ => None,
+    }
+}
+
+/// Replace in the text runs of the xml parts of a docx, pptx or xlsx file,
+/// a match may span several runs of a paragraph (formatting changes), the replacement goes in the first one
+pub fn replace_in_office(file_path: &str, extension: &str, regex: &Regex, replace_option: &ReplaceOption) -> Result<Option<FileReplacement>> {
+    let Some(format) = office_format(extension) else {
+        return Ok(None);
+    };
+    let mut archive = ZipArchive::new(BufReader::new(File::open(file_path)?))?;
+
+    let mut parts = vec![];
+    let mut new_parts = vec![];
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if !(format.parts)(entry.name()) {
+            continue;
+        }
+        let mut xml = String::new();
+        entry.read_to_string(&mut xml)?;
+        if let Some((new_xml, part)) = replace_in_xml(&xml, &format, regex, replace_option)? {
+            new_parts.push((entry.name().to_string(), new_xml));
+            parts.push(PartReplacement {
+                path: format!("{file_path}{ARCHIVE_PATH_SEPARATOR}{}", entry.name()),
+                ..part
+            });
+        }
+    }
+    if parts.is_empty() {
+        return Ok(None);
+    }
+
+    // Modified parts are compressed again, the other entries are copied as is
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index)?;
+        match new_parts.iter().find(|(name, _)| name == entry.name()) {
+            Some((name, xml)) => {
+                let options = FileOptions::default()
+                    .compression_method(entry.compression())
+                    .last_modified_time(entry.last_modified());
+                drop(entry);
+                writer.start_file(name, options)?;
+                writer.write_all(xml.as_bytes())?;
+            },
+            None => writer.raw_copy_file(entry)?,
+        }
+    }
+    let content = writer.finish()?.into_inner();
+
+    Ok(Some(FileReplacement {
+        file_path: file_path.to_string(),
+        parts,
+        content,
+    }))
+}
+
+/// New xml and the paragraphs changed, None without match
+fn replace_in_xml(xml: &str, format: &OfficeFormat, regex: &Regex, replace_option: &ReplaceOption) -> Result<Option<(String, PartReplacement)>> {
+    let mut reader = Reader::from_str(xml);
+    // Runs of the paragraphs being read, paragraphs may be nested (text boxes)
+    let mut paragraphs: Vec<Vec<TextRun>> = vec![];
+    let mut in_text = false;
+    // Text of all the paragraphs, for the diff context
+    let mut lines = vec![];
+    let mut changes = vec![];
+    let mut count = 0;
+    // Byte ranges of the xml replaced, and their new content
+    let mut edits: Vec<(usize, usize, String)> = vec![];
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(ref e) if e.name().as_ref() == format.paragraph => paragraphs.push(vec![]),
+            Event::Start(ref e) if e.name().as_ref() == format.text => if let Some(runs) = paragraphs.last_mut() {
+                let tag_end = reader.buffer_position() as usize;
+                runs.push(TextRun {
+                    tag_end,
+                    preserve_space: e.try_get_attribute("xml:space").ok().flatten().is_some(),
+                    range: (tag_end, tag_end),
+                    text: String::new(),
+                });
+                in_text = true;
+            },
+            Event::Text(ref e) if in_text => if let Some(run) = paragraphs.last_mut().and_then(|runs| runs.last_mut()) {
+                run.range.1 = reader.buffer_position() as usize;
+                run.text.push_str(&e.unescape().map_err(xml_error)?);
+            },
+            Event::End(ref e) if e.name().as_ref() == format.text => in_text = false,
+            Event::End(ref e) if e.name().as_ref() == format.paragraph => if let Some(runs) = paragraphs.pop() {
+                let text: String = runs.iter().map(|run| run.text.as_str()).collect();
+                let (new_texts, found) = replace_in_runs(&runs, &text, regex, replace_option);
+                if found > 0 {
+                    count += found;
+                    changes.push(Change {
+                        line: lines.len(),
+                        old: vec![text.clone()],
+                        new: vec![new_texts.concat()],
+                    });
+                    for (run, new_text) in runs.iter().zip(new_texts) {
+                        if new_text == run.text {
+                            continue;
+                        }
+                        // Leading and trailing spaces are dropped without xml:space="preserve"
+                        if !run.preserve_space && format.text == b"w:t" && new_text.trim() != new_text {
+                            edits.push((run.tag_end - 1, run.tag_end - 1, String::from(" xml:space=\"preserve\"")));
+                        }
+                        edits.push((run.range.0, run.range.1, partial_escape(&new_text).to_string()));
+                    }
+                }
+                lines.push(text);
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    if count == 0 {
+        return Ok(None);
+    }
+
+    let mut new_xml = xml.to_string();
+    edits.sort_by_key(|(start, end, _)| (*start, *end));
+    for (start, end, content) in edits.into_iter().rev() {
+        new_xml.replace_range(start..end, &content);
+    }
+    Ok(Some((new_xml, PartReplacement {
+        path: String::new(),
+        lines,
+        changes,
+        count,
+    })))
+}
+
+/// New text of each run and the count of matches: the text between the matches stays in its run,
+/// the replacement goes in the run where the match starts
+fn replace_in_runs(runs: &[TextRun], text: &str, regex: &Regex, replace_option: &ReplaceOption) -> (Vec<String>, usize) {
+    let mut bounds = vec![];
+    let mut offset = 0;
+    for run in runs {
+        bounds.push((offset, offset + run.text.len()));
+        offset += run.text.len();
+    }
+    let mut new_texts = vec![String::new(); runs.len()];
+    let copy = |new_texts: &mut Vec<String>, start: usize, end: usize| {
+        for (index, (run_start, run_end)) in bounds.iter().enumerate() {
+            if start.max(*run_start) < end.min(*run_end) {
+                new_texts[index].push_str(&text[start.max(*run_start)..end.min(*run_end)]);
+            }
+        }
+    };
+
+    let mut count = 0;
+    let mut last = 0;
+    for captures in regex.captures_iter(text) {
+        let found = captures.get(0).unwrap();
+        copy(&mut new_texts, last, found.start());
+        let run_index = bounds
+            .iter()
+            .position(|(start, end)| *start <= found.start() && found.start() < *end)
+            .unwrap_or(runs.len().saturating_sub(1));
+        if let Some(new_text) = new_texts.get_mut(run_index) {
+            replace_option.expand(&captures, new_text);
+        }
+        last = found.end();
+        count += 1;
+    }
+    copy(&mut new_texts, last, text.len());
+    (new_texts, count)
+}
+
+fn xml_error(err: quick_xml::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn office_replace_runs() {
+        let xml = r#"<w:document><w:body><w:p><w:r><w:t>Acme</w:t></w:r><w:r><w:t xml:space="preserve"> Corp &amp; co</w:t></w:r></w:p><w:p><w:r><w:t>Other</w:t></w:r></w:p></w:body></w:document>"#;
+        let replace_option = ReplaceOption::new(String::from("Acme Corp"), String::from("Globex <Inc>"));
+        let format = office_format("docx").unwrap();
+        let (new_xml, part) = replace_in_xml(xml, &format, &replace_option.regex().unwrap(), &replace_option).unwrap().unwrap();
+        assert_eq!(
+            r#"<w:document><w:body><w:p><w:r><w:t>Globex &lt;Inc&gt;</w:t></w:r><w:r><w:t xml:space="preserve"> &amp; co</w:t></w:r></w:p><w:p><w:r><w:t>Other</w:t></w:r></w:p></w:body></w:document>"#,
+            new_xml
+        );
+        assert_eq!(1, part.count);
+        assert_eq!(vec!["Acme Corp & co", "Other"], part.lines);
+        assert_eq!(vec![Change { line: 0, old: vec!["Acme Corp & co".to_string()], new: vec!["Globex <Inc> & co".to_string()] }], part.changes);
+
+        // Spaces kept at the end of a run
+        let replace_option = ReplaceOption::new(String::from("Corp"), String::from(" "));
+        let (new_xml, _) = replace_in_xml("<w:p><w:t>Acme Corp</w:t></w:p>", &format, &replace_option.regex().unwrap(), &replace_option).unwrap().unwrap();
+        assert_eq!(r#"<w:p><w:t xml:space="preserve">Acme  </w:t></w:p>"#, new_xml);
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::{Error, ErrorKind};
+use regex::{Captures, Regex, RegexBuilder};
+use crate::app::commands::search::option::SearchOption;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+pub struct ReplaceOption {
+    /// Regular expression, or text if literal
+    pub pattern: String,
+    /// Replacement text, with $1, ${name} for the capture groups
+    pub replacement: String,
+    /// Write the changes, otherwise only display the diff
+    pub apply: bool,
+    /// Keep the original file as <file>.bak
+    pub backup: bool,
+    pub literal: bool,
+    pub ignore_case: bool,
+    /// Lines around the changes in the diff
+    pub context: usize,
+    /// Files selection, same as the search command (extensions, files, walk, filters, thread)
+    pub search_option: SearchOption,
+}
+
+impl ReplaceOption {
+    pub fn new(pattern: String, replacement: String) -> Self {
+        ReplaceOption {
+            pattern,
+            replacement,
+            apply: false,
+            backup: false,
+            literal: false,
+            ignore_case: false,
+            context: 3,
+            search_option: SearchOption::new(String::new()),
+        }
+    }
+
+    pub fn set_apply(&mut self) {
+        self.apply = true;
+    }
+
+    pub fn set_backup(&mut self) {
+        self.backup = true;
+    }
+
+    pub fn set_literal(&mut self) {
+        self.literal = true;
+    }
+
+    pub fn set_ignore_case(&mut self) {
+        self.ignore_case = true;
+    }
+
+    pub fn set_context(&mut self, value: &str) -> Result<()> {
+        match value.parse::<usize>() {
+            Ok(v) => {
+                self.context = v;
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid value for context"
+            ))
+        }
+    }
+
+    /// Pattern compiled in multi-line mode: ^ and $ match at the start and end of each line
+    pub fn regex(&self) -> Result<Regex> {
+        let pattern = if self.literal { regex::escape(&self.pattern) } else { self.pattern.clone() };
+        RegexBuilder::new(&pattern)
+            .multi_line(true)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .map_err(|err| Error::new(
+                ErrorKind::InvalidInput,
+                format!("\nreplace error: invalid pattern {}: {err}\n", self.pattern)
+            ))
+    }
+
+    /// Replacement of a match, capture groups are expanded unless literal
+    pub fn expand(&self, captures: &Captures, dst: &mut String) {
+        if self.literal {
+            dst.push_str(&self.replacement);
+        } else {
+            captures.expand(&self.replacement, dst);
+        }
+    }
+}
+
+impl Clone for ReplaceOption {
+    fn clone(&self) -> Self {
+        ReplaceOption {
+            pattern: self.pattern.clone(),
+            replacement: self.replacement.clone(),
+            apply: self.apply,
+            backup: self.backup,
+            literal: self.literal,
+            ignore_case: self.ignore_case,
+            context: self.context,
+            search_option: self.search_option.clone(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use regex::Regex;
+use super::{diff::Change, option::ReplaceOption};
+
+/// Lines changed by a group of matches: first line, last line, and each match (start, end, replacement)
+type Block = (usize, usize, Vec<(usize, usize, String)>);
+
+/// Text with the matches replaced, the count of matches and the lines changed
+pub struct TextReplacement {
+    pub text: String,
+    pub count: usize,
+    pub changes: Vec<Change>,
+}
+
+/// Replace all the matches of the pattern in text,
+/// the lines containing a match (or a part of a multi-line match) are reported as changed
+pub fn replace_text(text: &str, regex: &Regex, replace_option: &ReplaceOption) -> TextReplacement {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset) - 1;
+    let line_end = |line: usize| line_starts.get(line + 1).map_or(text.len(), |next| next - 1);
+
+    // Matches grouped by the lines they change
+    let mut blocks: Vec<Block> = vec![];
+    let mut count = 0;
+    for captures in regex.captures_iter(text) {
+        let found = captures.get(0).unwrap();
+        let mut replacement = String::new();
+        replace_option.expand(&captures, &mut replacement);
+        let (first_line, last_line) = (line_of(found.start()), line_of(found.end()));
+        match blocks.last_mut() {
+            Some((_, end, matches)) if first_line <= *end => {
+                *end = last_line;
+                matches.push((found.start(), found.end(), replacement));
+            },
+            _ => blocks.push((first_line, last_line, vec![(found.start(), found.end(), replacement)])),
+        }
+        count += 1;
+    }
+
+    let mut replaced = String::with_capacity(text.len());
+    let mut changes = vec![];
+    let mut last = 0;
+    for (first_line, last_line, matches) in blocks {
+        let (block_start, block_end) = (line_starts[first_line], line_end(last_line));
+        let mut new_block = String::new();
+        let mut cursor = block_start;
+        for (start, end, replacement) in matches {
+            new_block.push_str(&text[cursor..start]);
+            new_block.push_str(&replacement);
+            cursor = end;
+        }
+        new_block.push_str(&text[cursor..block_end]);
+
+        replaced.push_str(&text[last..block_start]);
+        replaced.push_str(&new_block);
+        last = block_end;
+        changes.push(Change {
+            line: first_line,
+            old: text[block_start..block_end].split('\n').map(String::from).collect(),
+            new: new_block.split('\n').map(String::from).collect(),
+        });
+    }
+    replaced.push_str(&text[last..]);
+
+    TextReplacement {
+        text: replaced,
+        count,
+        changes,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replace_text_lines() {
+        let replace_option = ReplaceOption::new(String::from(r"(\w+) Corp"), String::from("$1 Inc."));
+        let text = "Acme Corp\nno match\nAcme Corp and Foo Corp\n";
+        let replacement = replace_text(text, &replace_option.regex().unwrap(), &replace_option);
+        assert_eq!("Acme Inc.\nno match\nAcme Inc. and Foo Inc.\n", replacement.text);
+        assert_eq!(3, replacement.count);
+        assert_eq!(vec![
+            Change { line: 0, old: vec!["Acme Corp".to_string()], new: vec!["Acme Inc.".to_string()] },
+            Change { line: 2, old: vec!["Acme Corp and Foo Corp".to_string()], new: vec!["Acme Inc. and Foo Inc.".to_string()] },
+        ], replacement.changes);
+
+        // A removed line
+        let mut replace_option = ReplaceOption::new(String::from("draft\n"), String::new());
+        replace_option.set_literal();
+        let replacement = replace_text("a\ndraft\nb", &replace_option.regex().unwrap(), &replace_option);
+        assert_eq!("a\nb", replacement.text);
+        assert_eq!(vec![Change { line: 1, old: vec!["draft".to_string(), "b".to_string()], new: vec!["b".to_string()] }], replacement.changes);
+    }
+}
@@ -1,32 +1,51 @@
 pub mod option;
 pub mod text;
 pub mod pdf;
+pub mod epub;
 pub mod ms;
+pub mod mail;
+pub mod markup;
 pub mod video;
 pub mod image;
 pub mod audio;
+pub mod result;
+pub mod archive;
+pub mod document;
+pub mod elastic;
+pub mod index;
+pub mod stats;
 
 
-use std::collections::HashMap;
-use std::io::{Error, ErrorKind};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Error, ErrorKind, IsTerminal};
 use std::fs;
-use std::path::Path;
-use std::sync::mpsc::{self, Sender};
+use std::sync::{mpsc::{self, Sender}, Arc};
+use std::time::Instant;
 use std::cmp;
 
+use self::archive::ArchiveSearch;
 use self::audio::AudioSearch;
+use self::document::Document;
+use self::elastic::ElasticSearch;
+use self::epub::EpubSearch;
 use self::image::ImageSearch;
+use self::mail::MailSearch;
+use self::markup::MarkupSearch;
+use self::index::SearchIndex;
+use self::stats::SearchStats;
 use self::video::VideoSearch;
 
-use super::{get_args_parameter, Runnable, OPTION_SEPARATOR};
-use crate::helpers::output::colorize;
+use super::{get_args_parameter, NoMatch, Runnable, OPTION_SEPARATOR};
+use crate::helpers::interrupt;
+use crate::helpers::output::colorize_ranges;
+use crate::helpers::query::QueryMatch;
 use crate::helpers::threadpool::ThreadPool;
-use crate::helpers::file::{self, get_file_name, get_extension};
+use crate::helpers::file::{self, archive::{archive_format, ARCHIVE_PATH_SEPARATOR}, get_extension, magic, walk::walk};
 use colored::Colorize;
-use diacritics::remove_diacritics;
+use serde_json::json;
 use option::SearchOption;
+use result::{SearchHit, SearchResult};
 use pdf::PdfSearch;
-use regex::Regex;
 use text::TextSearch;
 use ms::MsSearch;
 
@@ -44,19 +63,32 @@ type Result<T> = std::result::Result<T, std::io::Error>;
 /// ## Features
 /// 
 /// * [o] Search in file
-///     * [x] text file
+///     * [x] text file (UTF-8, UTF-16, Latin-1), binary files skipped
+///     * [x] file type read from the content (magic bytes), the extension is the fallback
 ///     * [o] pdf: TODO: ?Identity-H Unimplemented?
+///     * [x] epub
 ///     * [x] office file
 ///         * [x] docx
 ///         * [x] xlsx
 ///         * [x] pptx
+///         * [x] odt, ods, odp
+///         * [x] doc, xls, ppt (97-2003)
+///     * [x] email (eml, mbox): headers, text parts and attachments, hits reported by message
+///     * [x] html, xml, markdown, reStructuredText: text without the markup, hits reported with the nearest heading
 ///     * [x] Search in video
+///         * [x] subtitles (srt, vtt, ass next to the video, embedded text streams)
+///     * [x] Search in archive (zip, tar, tar.gz)
 /// * [x] Search in directory
+/// * [x] Sort results by relevance, path, modification time or size
+/// * [x] Search in the index (oms index), without reading the files
+/// * [x] Search in the Elasticsearch catalog (--elastic-url), without reading the files
+/// * [x] Progress on stderr, summary with the slowest and failed files, Ctrl-C prints the partial results
 /// * [o] Search arguments
 ///     * [x] extensions
 ///     * [x] exlude file
 ///     * [x] exlude extension
-///     * [x] display file-only
+///     * [x] display file-only (-l), files without match (-L), count (-c), quiet (-q)
+///     * [x] exit code 0 (matched), 1 (no match), 2 (error)
 ///     * [x] thread
 pub struct Search {
     /// path of the file to search in
@@ -79,11 +111,40 @@ impl Runnable for Search {
             return Ok(());
         }
 
+        if self.cmd_options.contains_key("elastic-url") {
+            elastic::check_options(&self.cmd_options)?;
+        }
         for (option, value) in &self.cmd_options {
             match option.as_str() {
                 "p" | "provider" => search_option.set_provider(value)?,
                 "display" => search_option.set_display(value)?,
+                "fuzzy" => search_option.set_fuzzy(value)?,
+                "format" => search_option.set_format(value)?,
+                "A" | "after-context" => search_option.set_after_context(value)?,
+                "B" | "before-context" => search_option.set_before_context(value)?,
+                "C" | "context" => search_option.set_context(value)?,
                 "t" | "thread" => search_option.set_thread(value)?,
+                "binary" => search_option.set_binary(value)?,
+                "c" | "count" => search_option.set_count(),
+                "l" | "files-with-matches" => search_option.set_files_with_matches(),
+                "L" | "files-without-match" => search_option.set_files_without_match(),
+                "q" | "quiet" => search_option.set_quiet(),
+                "index" => search_option.set_index(),
+                "elastic-url" => search_option.set_elastic(value)?,
+                "sort" => search_option.set_sort(value)?,
+                "max-results" => search_option.set_max_results(value)?,
+                "max-per-file" => search_option.set_max_per_file(value)?,
+                "archive-depth" => search_option.set_archive_depth(value)?,
+                "max-depth" => search_option.walk.set_max_depth(value)?,
+                "hidden" => search_option.walk.set_hidden(),
+                "follow-symlinks" => search_option.walk.set_follow_symlinks(),
+                "min-size" => search_option.filter.set_min_size(value)?,
+                "max-size" => search_option.filter.set_max_size(value)?,
+                "newer-than" => search_option.filter.set_newer_than(value)?,
+                "older-than" => search_option.filter.set_older_than(value)?,
+                "min-duration" => search_option.filter.set_min_duration(value)?,
+                "codec" => search_option.filter.set_codec(value)?,
+                "resolution" => search_option.filter.set_resolution(value)?,
                 "e" | "extensions" => search_option.extensions_from(value)?,
                 "exclude-extensions" => search_option.exclude_extensions_from(value)?, 
                 "f" | "files" => search_option.files_from(value)?, 
@@ -97,14 +158,34 @@ impl Runnable for Search {
             };
         }
 
+        search_option.base_path = self.file_path.clone();
         let thread_pool = ThreadPool::new(search_option.thread);
+        let stats = Arc::new(SearchStats::new());
+        // -q stops at the first file to list: matched, or not matched with -L
+        match (search_option.quiet, search_option.files_without_match) {
+            (true, true) => stats.stop_on_unmatched(),
+            (true, false) => stats.stop_on_match(),
+            _ => (),
+        }
+        // Ctrl-C stops queuing files, the files being read are searched, then the results found are printed
+        interrupt::handle_interrupt();
+        let progress = (io::stderr().is_terminal() && !search_option.quiet).then(|| SearchStats::show_progress(&stats));
 
         match fs::metadata(&self.file_path) {
+            // The catalog is searched on the server, the path is not read
+            _ if search_option.elastic.is_some() => {
+                ElasticSearch {
+                    search_option: &search_option,
+                }.search(tx.clone(), &stats)?;
+            },
+            Ok(_) if search_option.index => {
+                search_in_index(&self.file_path, &search_option, tx.clone(), &stats)?;
+            },
             Ok(md) if md.is_file() => {
-                search_in_file(&self.file_path, &self.search_term, &search_option, &thread_pool, tx.clone());
+                search_in_file(&self.file_path, &search_option, &thread_pool, tx.clone(), &stats);
             },
             Ok(md) if md.is_dir() => {
-                search_in_dir(&self.file_path, &self.search_term, &search_option, &thread_pool, tx.clone());
+                search_in_dir(&self.file_path, &search_option, &thread_pool, tx.clone(), &stats);
             },
             Ok(_) => return Err(Error::new(
                 ErrorKind::InvalidInput, 
@@ -119,125 +200,518 @@ impl Runnable for Search {
         };
 
         drop(tx);
-        for message in rx {
-            println!("{message}");
+        let results = sort_results(rx.into_iter().collect(), &search_option);
+        stats.finish();
+        if let Some(progress) = progress {
+            progress.join().unwrap_or_default();
+        }
+        // Exit code 0 if files are listed (matched, or not matched with -L), 1 otherwise
+        let found = match search_option.files_without_match {
+            true => !stats.files_without_match().is_empty(),
+            false => stats.matched_count() > 0,
+        };
+        if search_option.quiet {
+            return if found { Ok(()) } else { Err(Error::other(NoMatch)) };
+        }
+        print_results(results, &search_option, &stats);
+
+        if search_option.has_summary() {
+            eprintln!("\n{}", stats.summary().dimmed());
         }
-        Ok(())
+        if interrupt::is_interrupted() {
+            eprintln!("{}", "Interrupted: partial results".yellow());
+        }
+        if found { Ok(()) } else { Err(Error::other(NoMatch)) }
+    }
+
+    /// Exit code 2 on error, 1 being no match
+    fn error_code(&self) -> i32 {
+        2
     }
 }
 
 
-fn search_in_dir(dir_path: &String, search_term: &String, search_option: &SearchOption, thread_pool: &ThreadPool, tx: Sender<String>) {
-    let dir_path = dir_path.clone();
-    let search_term = search_term.clone();
+fn search_in_dir(dir_path: &str, search_option: &SearchOption, thread_pool: &ThreadPool, tx: Sender<SearchResult>, stats: &Arc<SearchStats>) {
+    walk(dir_path, &search_option.walk, |file_path| {
+        search_in_file(&file_path, search_option, thread_pool, tx.clone(), stats)
+    });
+}
+
+fn search_in_file(file_path: &str, search_option: &SearchOption, thread_pool: &ThreadPool, tx: Sender<SearchResult>, stats: &Arc<SearchStats>) {
+    let file_path = file_path.to_string();
     let search_option = search_option.clone();
+    let stats = Arc::clone(stats);
 
-    for entry in fs::read_dir(Path::new(&dir_path)).unwrap() {
-        let path = entry.unwrap().path();
-        if path.is_file() {
-            search_in_file(&path.to_str().unwrap().to_string(), &search_term, &search_option, thread_pool, tx.clone())
-        } else if path.is_dir() {
-            search_in_dir(&path.to_str().unwrap().to_string(), &search_term, &search_option, thread_pool, tx.clone())
+    // Type of the file detected once, from its content
+    let extension = magic::media_extension(&file_path);
+    let archive = archive_format(&file_path, &extension).filter(|_| search_option.archive_depth > 0);
+    if let Some(format) = archive {
+        // Filters on extensions and file names apply to the files inside
+        if is_excluded(&file_path, &search_option) {
+            stats.skip();
+            return;
         }
+        stats.queue();
+        thread_pool.execute(move || {
+            // Queued files are dropped after Ctrl-C
+            if interrupt::is_interrupted() {
+                return;
+            }
+            if !search_option.filter.is_match(&file_path) {
+                stats.skip();
+                return;
+            }
+            let start = Instant::now();
+            ArchiveSearch {
+                file_path: &file_path,
+                display_path: &file_path,
+                format,
+                search_option: &search_option,
+                depth: 1,
+            }.search(tx, &stats);
+            stats.scan(&file_path, file::file_size(&file_path).unwrap_or_default(), start.elapsed(), vec![]);
+        });
+        return;
+    }
+    if !is_searchable(&file_path, &search_option) {
+        stats.skip();
+        return;
     }
+
+    stats.queue();
+    thread_pool.execute(move || {
+        if interrupt::is_interrupted() {
+            return;
+        }
+        // Filters reading the file (size, date, ffprobe) run in the thread
+        if search_option.filter.is_match(&file_path) {
+            search_file_content(&file_path, &extension, &search_option, tx, &stats);
+        } else {
+            stats.skip();
+        }
+    });
 }
 
-fn search_in_file(file_path: &String, search_term: &String, search_option: &SearchOption, thread_pool: &ThreadPool, tx: Sender<String>) {
-    let file_path = file_path.clone();
-    let search_term = remove_diacritics(&search_term.to_lowercase().clone());
-    let search_option = search_option.clone();
+/// Check the extension and file name filters
+pub fn is_searchable(file_path: &str, search_option: &SearchOption) -> bool {
+    let extension = get_extension(file_path).to_lowercase();
 
-    let extension = get_extension(&file_path).to_lowercase();
-    let file_name = get_file_name(&file_path).to_lowercase();
-    
-    if !search_option.has_extension(&extension) {
-        return ();
+    search_option.has_extension(&extension) && search_option.has_file(file_path) && !is_excluded(file_path, search_option)
+}
+
+pub fn is_excluded(file_path: &str, search_option: &SearchOption) -> bool {
+    let extension = get_extension(file_path).to_lowercase();
+
+    search_option.is_extension_excluded(&extension) || search_option.is_file_excluded(file_path)
+}
+
+/// Search in a file with the handler of its type
+fn search_file_content(file_path: &String, extension: &str, search_option: &SearchOption, tx: Sender<SearchResult>, stats: &SearchStats) {
+    let start = Instant::now();
+    // Text and mail files are searched as they are read, the other files once extracted
+    let searched = if file::MAIL_EXTENSIONS.contains(&extension) {
+        Some(MailSearch {
+            file_path,
+            search_option,
+        }.search())
+    } else if let Some(document) = media_document(file_path, extension, search_option) {
+        Some((document.search(search_option), document.errors))
+    } else {
+        TextSearch {
+            file_path,
+            search_option,
+        }.search()
+    };
+    // Binary files skipped (--binary=skip) are neither scanned nor listed by -L
+    let Some((result, errors)) = searched else {
+        stats.skip();
+        return;
+    };
+    stats.scan(file_path, file::file_size(file_path).unwrap_or_default(), start.elapsed(), errors);
+    match result {
+        Some(result) => {
+            stats.matched();
+            tx.send(result).unwrap_or_default();
+        },
+        None => stats.unmatched(file_path),
     }
-    if search_option.is_extension_excluded(&extension) {
-        return ();
+}
+
+/// Extract the text of a file with the handler of its type
+///
+/// `extension` is the type of the file detected by magic::media_extension,
+/// None for the binary files skipped (--binary=skip)
+pub fn file_document(file_path: &String, extension: &str, search_option: &SearchOption) -> Option<Document> {
+    media_document(file_path, extension, search_option).or_else(|| TextSearch {
+        file_path,
+        search_option,
+    }.document())
+}
+
+/// Document of a pdf, epub, video, audio, office, mail, markup or image file, None for the other files (text)
+fn media_document(file_path: &String, extension: &str, search_option: &SearchOption) -> Option<Document> {
+    let is = |extensions: &[&str]| extensions.contains(&extension);
+    let document = if is(&file::PDF_EXTENSIONS) {
+        PdfSearch {
+            file_path,
+            search_option,
+        }.document()
     }
-    if !search_option.has_file(&file_name) {
-        return ();
-    }        
-    if search_option.is_file_excluded(&file_name) {
-        return ();
+    else if is(&file::EPUB_EXTENSIONS) {
+        EpubSearch {
+            file_path,
+            search_option,
+        }.document()
     }
-    
-    thread_pool.execute(move || {
-        if file::is_pdf_file(&file_path) {
-            PdfSearch { 
-                file_path: &file_path, 
-                search_term: &search_term, 
-                search_option: &search_option,
-            }.search(tx);
-        }
-        else if file::is_video_file(&file_path) {
-            VideoSearch {
-                file_path: &file_path, 
-                search_term: &search_term, 
-                search_option: &search_option,
-            }.search(tx);
-        }
-        else if file::is_audio_file(&file_path) {
-            AudioSearch { 
-                file_path: &file_path, 
-                search_term: &search_term, 
-                search_option: &search_option,
-            }.search(tx);
-        }
-        else if file::is_ms_file(&file_path) {
-            MsSearch { 
-                file_path: &file_path, 
-                search_term: &search_term, 
-                search_option: &search_option,
-            }.search(tx);
+    else if is(&file::VIDEO_EXTENSIONS) {
+        VideoSearch {
+            file_path,
+            search_option,
+        }.document()
+    }
+    else if is(&file::AUDIO_EXTENSIONS) {
+        AudioSearch {
+            file_path,
+            search_option,
+        }.document()
+    }
+    else if is(&file::MS_EXTENSIONS) {
+        MsSearch {
+            file_path,
+            extension,
+            search_option,
+        }.document()
+    }
+    else if is(&file::MAIL_EXTENSIONS) {
+        MailSearch {
+            file_path,
+            search_option,
+        }.document()
+    }
+    else if is(&file::MARKUP_EXTENSIONS) {
+        MarkupSearch {
+            file_path,
+            search_option,
+        }.document()
+    }
+    else if is(&file::IMAGE_EXTENSIONS) {
+        ImageSearch {
+            file_path,
+            search_option,
+        }.document()
+    }
+    else {
+        return None;
+    };
+    Some(document)
+}
+
+/// Search in the documents stored by the index command, files are not read
+fn search_in_index(path: &String, search_option: &SearchOption, tx: Sender<SearchResult>, stats: &SearchStats) -> Result<()> {
+    let index = SearchIndex::load()?;
+    if index.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "\nsearch error: the index is empty, run 'oms index --cache-path=<dir> <dir>' first\n"
+        ));
+    }
+    // Paths are indexed absolute, results are displayed from the given path
+    let root = fs::canonicalize(path)?.display().to_string();
+    let mut search_option = search_option.clone();
+    search_option.base_path = root.clone();
+    let search_option = &search_option;
+
+    for (id, document_path) in index.candidates(&search_option.query, &root) {
+        if interrupt::is_interrupted() {
+            break;
         }
-        else if file::is_image_file(&file_path) {
-            ImageSearch { 
-                file_path: &file_path, 
-                search_term: &search_term, 
-                search_option: &search_option,
-            }.search(tx);
+        if !is_searchable(&document_path, search_option) || !search_option.filter.is_match(disk_path(&document_path)) {
+            stats.skip();
+            continue;
         }
-        else {
-            TextSearch {
-                file_path: &file_path,
-                search_term: &search_term,
-                search_option: &search_option,
-            }.search(tx);
+        let start = Instant::now();
+        match index.load_document(id) {
+            Ok(mut document) => {
+                stats.scan(&document_path, 0, start.elapsed(), std::mem::take(&mut document.errors));
+                document.file_path = match &document_path[root.len()..] {
+                    "" => path.to_string(),
+                    relative_path => format!("{}{relative_path}", path.trim_end_matches('/')),
+                };
+                match document.search(search_option) {
+                    Some(result) => {
+                        stats.matched();
+                        tx.send(result).unwrap_or_default();
+                    },
+                    None => stats.unmatched(&document.file_path),
+                }
+            },
+            Err(err) => stats.fail(&document_path, vec![err.to_string()]),
         }
-    });
+    }
+    Ok(())
+}
+
+/// Text found in a content
+struct TextWindow {
+    text: String,
+    /// Byte offset of text in the line
+    start: usize,
+    /// Byte ranges of the terms in text
+    offsets: Vec<(usize, usize)>,
+    /// Line number in the content, from 1
+    line: usize,
+    context_before: Vec<String>,
+    context_after: Vec<String>,
 }
 
-fn text_reg_contains(content: &String, search_term: &String) -> Option<Vec<String>> {
-    let str_re = format!(r"(?im)(.{{0,100}}{}.{{0,100}})", search_term);
-    let re = Regex::new(str_re.as_str()).unwrap();
+/// Find query terms in each line of content,
+/// returns the text around each match (100 chars before and after), and the lines around (context)
+fn text_query_contains(content: &str, field: &str, query_match: &mut QueryMatch, context: (usize, usize)) -> Option<Vec<TextWindow>> {
+    let (before_count, after_count) = context;
+    let lines: Vec<&str> = content.lines().collect();
     let mut results = vec![];
-    re
-        .captures_iter(&remove_diacritics(content))
-        .map(|c| c.extract())
-        .for_each(|(_, [c])| results.push(c.to_string()));
-    if results.len() == 0 {
+    for (index, line) in lines.iter().enumerate() {
+        let ranges = query_match.find(field, line);
+        let mut window_end = 0;
+        for (start, end) in &ranges {
+            if *start < window_end {
+                continue;
+            }
+            let window_start = line[..*start].char_indices().rev().nth(99).map_or(0, |(i, _)| i).max(window_end);
+            window_end = line[*end..].char_indices().nth(100).map_or(line.len(), |(i, _)| end + i);
+            let offsets = ranges
+                .iter()
+                .filter(|(s, e)| *s >= window_start && *e <= window_end)
+                .map(|(s, e)| (s - window_start, e - window_start))
+                .collect();
+            results.push(TextWindow {
+                text: line[window_start..window_end].to_string(),
+                start: window_start,
+                offsets,
+                line: index + 1,
+                context_before: lines[index.saturating_sub(before_count)..index].iter().map(|l| l.to_string()).collect(),
+                context_after: lines.iter().skip(index + 1).take(after_count).map(|l| l.to_string()).collect(),
+            });
+        }
+    }
+    if results.is_empty() {
         return None;
     }
     Some(results)
 }
 
+/// Rows at (page, line): hits found, and context text with the hit it comes from
+type RowBlock<'a> = BTreeMap<(usize, usize), (Vec<&'a SearchHit>, Option<(&'a str, &'a SearchHit)>)>;
+
+/// Hit or context line to display
+enum ResultRow<'a> {
+    Hit(&'a SearchHit),
+    /// Item (line number, page, chapter) and text of a line around a hit
+    Context(String, &'a str),
+    /// Between non contiguous context blocks
+    Separator,
+}
+
+/// Hits of a file with their context lines,
+/// ordered by page and line, overlapping context are merged
+fn result_rows<'a>(result: &'a SearchResult, search_option: &SearchOption) -> Vec<ResultRow<'a>> {
+    let mut rows = vec![];
+    // (page, line) -> (hits, context text)
+    let mut block: RowBlock = BTreeMap::new();
+
+    for hit in &result.hits {
+        // Fields (title, summary...) have no position
+        if hit.line == 0 {
+            push_rows(&mut rows, std::mem::take(&mut block), search_option);
+            rows.push(ResultRow::Hit(hit));
+            continue;
+        }
+        block.entry((hit.page, hit.line)).or_default().0.push(hit);
+        let first_line = hit.line - hit.context_before.len();
+        for (i, text) in hit.context_before.iter().enumerate() {
+            block.entry((hit.page, first_line + i)).or_default().1 = Some((text, hit));
+        }
+        for (i, text) in hit.context_after.iter().enumerate() {
+            block.entry((hit.page, hit.line + 1 + i)).or_default().1 = Some((text, hit));
+        }
+    }
+    push_rows(&mut rows, block, search_option);
+    rows
+}
+
+fn push_rows<'a>(rows: &mut Vec<ResultRow<'a>>, block: RowBlock<'a>, search_option: &SearchOption) {
+    let mut previous: Option<(usize, usize)> = None;
+    for ((page, line), (hits, context)) in block {
+        if search_option.has_context() && previous.is_some_and(|(p, l)| p != page || l + 1 != line) {
+            rows.push(ResultRow::Separator);
+        }
+        if let (true, Some((text, origin))) = (hits.is_empty(), context) {
+            let item = if origin.location == "line" { line.to_string() } else { origin.item() };
+            rows.push(ResultRow::Context(item, text));
+        } else {
+            rows.extend(hits.into_iter().map(ResultRow::Hit));
+        }
+        previous = Some((page, line));
+    }
+}
+
+/// Order the results (--sort), whatever the order the files were searched in,
+/// then apply the limits (--max-results, --max-per-file)
+fn sort_results(mut results: Vec<SearchResult>, search_option: &SearchOption) -> Vec<SearchResult> {
+    // The catalog results come in the relevance order of the server query, kept unless --sort is given
+    let server_order = search_option.elastic.is_some() && search_option.sort.is_empty();
+    if !server_order {
+        // Ties are ordered by path to keep the same output between runs
+        results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    }
+    match search_option.sort.as_str() {
+        "relevance" => {
+            let mut scored: Vec<(f32, SearchResult)> = results.into_iter().map(|r| (r.relevance(), r)).collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            results = scored.into_iter().map(|(_, r)| r).collect();
+        },
+        "mtime" => results.sort_by_cached_key(|r| cmp::Reverse(file::get_creation_time(disk_path(&r.file_path)))),
+        "size" => results.sort_by_cached_key(|r| cmp::Reverse(file::file_size(disk_path(&r.file_path)).unwrap_or_default())),
+        _ => (),
+    }
+
+    if search_option.max_results > 0 {
+        results.truncate(search_option.max_results);
+    }
+    if search_option.max_per_file > 0 {
+        for result in results.iter_mut() {
+            result.hits.truncate(search_option.max_per_file);
+        }
+    }
+    results
+}
+
+/// Path of the file on the disk, the archive for a file inside an archive
+fn disk_path(file_path: &str) -> &str {
+    file_path.split(ARCHIVE_PATH_SEPARATOR).next().unwrap_or(file_path)
+}
+
+/// Print results in the required format
+fn print_results(results: Vec<SearchResult>, search_option: &SearchOption, stats: &SearchStats) {
+    // -L, -l, -c: one line (or json object) by file
+    let files: Option<Vec<(String, serde_json::Value)>> = if search_option.files_without_match {
+        Some(stats.files_without_match().into_iter().map(|file_path| (file_path.clone(), json!({ "file_path": file_path }))).collect())
+    } else if search_option.files_with_matches {
+        Some(results.iter().map(|r| (r.file_path.clone(), json!({ "file_path": r.file_path, "kind": r.kind }))).collect())
+    } else if search_option.count {
+        Some(results.iter().map(|r| (
+            format!("{}:{}", r.file_path, r.hits.len()),
+            json!({ "file_path": r.file_path, "kind": r.kind, "count": r.hits.len() }),
+        )).collect())
+    } else {
+        None
+    };
+    if let Some(files) = files {
+        print_files(files, &search_option.format);
+        return;
+    }
+
+    let mut hit_count = 0;
+    if search_option.format == "json" {
+        print!("[");
+    }
+    for result in results {
+        match search_option.format.as_str() {
+            "json" => for hit in &result.hits {
+                let separator = if hit_count == 0 { "" } else { "," };
+                print!("{separator}\n{}", serde_json::to_string(hit).unwrap_or_default());
+                hit_count += 1;
+            },
+            "ndjson" => for hit in &result.hits {
+                println!("{}", serde_json::to_string(hit).unwrap_or_default());
+            },
+            "grep" => print!("{}", format_grep(&result, search_option)),
+            _ => println!("{}", format_text(&result, search_option)),
+        }
+    }
+    if search_option.format == "json" {
+        println!("\n]");
+    }
+}
+
+/// Line of each file (text and grep formats), or its json object
+fn print_files(files: Vec<(String, serde_json::Value)>, format: &str) {
+    match format {
+        "json" => {
+            let objects: Vec<String> = files.iter().map(|(_, object)| format!("\n{object}")).collect();
+            println!("[{}\n]", objects.join(","));
+        },
+        "ndjson" => files.iter().for_each(|(_, object)| println!("{object}")),
+        _ => files.iter().for_each(|(line, _)| println!("{line}")),
+    }
+}
+
+fn format_text(result: &SearchResult, search_option: &SearchOption) -> String {
+    let mut output = format_file_display(&result.file_path);
+    for row in result_rows(result, search_option) {
+        match row {
+            ResultRow::Hit(hit) => output.push_str(&format_line_found(hit, search_option)),
+            ResultRow::Context(item, text) => output.push_str(&format_context_line(&item, text)),
+            ResultRow::Separator => output.push_str("--\n"),
+        }
+    }
+    output
+}
+
+/// path:line:text, without color, one hit per line
+fn format_grep(result: &SearchResult, search_option: &SearchOption) -> String {
+    let mut output = String::new();
+    for row in result_rows(result, search_option) {
+        match row {
+            ResultRow::Hit(hit) => output.push_str(&format!(
+                "{}:{}:{}\n",
+                result.file_path,
+                // Line number only, without the heading of markup files
+                if hit.location == "line" { hit.line.to_string() } else { hit.item() },
+                hit.text.replace(['\r', '\n'], " ")
+            )),
+            ResultRow::Context(item, text) => output.push_str(&format!(
+                "{}-{}-{}\n",
+                result.file_path,
+                item,
+                text.replace(['\r', '\n'], " ")
+            )),
+            ResultRow::Separator => output.push_str("--\n"),
+        }
+    }
+    output
+}
+
 fn format_file_display(file_path: &String) -> String {
     format!("{}\n", file_path.underline())
 }
 
-fn format_line_found<'a>(item: &'a String, text: &'a String, search_option: &SearchOption) -> String {
-    if search_option.display == "file-only" {
-        return "".to_string();
-    }
-    let re = Regex::new(format!(r"(?im){}", search_option.search_term).as_str()).unwrap();
-    let output = format!(
-        "{} -> {}\n",
-        item,
-        text.replace("\n", " ").trim().get(..cmp::min(500, text.len())).unwrap_or(text).trim()
-    );
-    colorize(&output, &re, (0, 102, 51)).unwrap_or(output)
+/// Item and text of a hit, only the terms found in the text are highlighted (not the item: Title, Page 3...)
+fn format_line_found(hit: &SearchHit, search_option: &SearchOption) -> String {
+    let line = hit.text.replace("\n", " ");
+    let trimmed = line.trim();
+    let text = trimmed.get(..cmp::min(500, trimmed.len())).unwrap_or(trimmed);
+    // Offsets of the hit in the displayed text
+    let start = line.len() - line.trim_start().len();
+    let ranges: Vec<(usize, usize)> = hit.offsets
+        .iter()
+        .filter(|(s, e)| *s >= start && *e <= start + text.len())
+        .map(|(s, e)| (s - start, e - start))
+        .collect();
+    let text = colorize_ranges(text, &ranges, (0, 102, 51)).unwrap_or(text.to_string());
+    let item = if search_option.query.is_fuzzy() {
+        format!("{} [{:.0}%]", hit.item(), hit.score * 100.)
+    } else {
+        hit.item()
+    };
+    format!("{item} -> {text}\n")
+}
+
+/// Line around a hit, not highlighted
+fn format_context_line(item: &str, text: &str) -> String {
+    let text = text.trim_end();
+    let text = text.get(..cmp::min(500, text.len())).unwrap_or(text);
+    format!("{}\n", format!("{item}  | {text}").dimmed())
 }
 
 /// Help message for this command
@@ -245,6 +719,14 @@ pub fn usage() -> String {
     format!("\
 search [options] <file_path|directory_path> <query>
     Search in file or directory. Display each line of the file containing the query text
+    Progress is displayed on stderr, then a summary with the slowest files and the files that could not be read.
+    Ctrl-C stops the search and displays the results found so far.
+    Query syntax:
+        foo bar, foo AND bar    files containing both terms
+        foo OR bar              files containing one of the terms
+        -foo, NOT foo           exclude files containing the term, the query may start with it: search <path> \"-draft report\"
+        \"exact phrase\"          search the whole phrase
+        <field>:foo             search only in field: title, cast, author, genre, summary, content, page, chapter, message, line, file
     Prerequisites:
         - [Optional] tesseract to search text inside images
         - [Optional] pdftotext to search text inside pdf
@@ -253,10 +735,42 @@ search [options] <file_path|directory_path> <query>
     -p <string> --provider=<string>   possible value: local, api (default), use for external information provider
     -e <string> --extensions=<string>    Search only in these file extensions, separated by '{OPTION_SEPARATOR}'
     --exclude-extensions=<string>    exlude these file extensions, separated by '{OPTION_SEPARATOR}'
-    -f <> --files=<string>  Search only in these file names
-    --exclude-files=<string>    exlude these files, separated by '{OPTION_SEPARATOR}'
-    --display=<string>  file-only|debug
+    -f <> --files=<string>  Search only in these files, separated by '{OPTION_SEPARATOR}': names, globs (*.min.js, **/build/**, *.{{jpg,png}}) or regex (re:^\\d{{4}}-)
+                            matching the file name or its path relative to the searched directory
+    --exclude-files=<string>    exlude these files (names, globs or regex), separated by '{OPTION_SEPARATOR}'
+    --min-size=<size> --max-size=<size>   Only files of this size, in bytes or with a unit: 500K, 4G
+    --newer-than=<date> --older-than=<date>  Only files modified after/before a date (2024-01-31) or a duration ago: 12h, 7d, 2w, 1m, 1y
+    --min-duration=<duration>   Only media lasting at least: 90 (seconds), 45min, 1h30min (ffprobe)
+    --codec=<string>    Only media with a stream using one of these codecs, separated by ',': h264,hevc,aac (ffprobe)
+    --resolution=<string>   Only videos at least this size: 720p, 1920x1080 (ffprobe)
+    --display=<string>  file-only (same as -l)|debug
+    -c --count  Display the number of hits of each file matched: path:count
+    -l --files-with-matches     Display only the paths of the files matched
+    -L --files-without-match    Display only the paths of the files searched without match
+    -q --quiet  Display nothing, stop at the first hit (at the first file searched without match with -L)
+    Exit status: 0 if a file is matched (not matched with -L), 1 otherwise, 2 on error
+    --format=<string>   text (default), json, ndjson (one json hit by line), grep (path:line:text without color)
+                        json offsets are byte ranges in the hit text, that starts at text_start in its line
+    -A <int> --after-context=<int>  Display <int> lines after each line found (text, pdf and epub)
+    -B <int> --before-context=<int> Display <int> lines before each line found (text, pdf and epub)
+    -C <int> --context=<int>        Display <int> lines before and after each line found (text, pdf and epub)
+    --binary=<string>   skip (default), name-only (search only the name), text (search binary files as text)
+    --sort=<string>     path (default), relevance (title and metadata first), mtime (newest first), size (largest first),
+                        the results of --elastic-url are in the relevance order of the server by default
+    --max-results=<int> Display only the first <int> files found
+    --max-per-file=<int>    Display only the first <int> hits of each file
+    --fuzzy[=<int>]     Typo tolerant search, max number of typos by term (default 2), display the similarity of each line found
     -t <int> --thread=<int>    Max thread number, default 1
+    --max-depth=<int>   Max depth of sub directories, 1 for the files of the given directory only
+    --hidden    Search also in hidden files and directories
+    --follow-symlinks   Follow symbolic links to directories
+    --index     Search in the index built by the index command (requires --cache-path), files are not read,
+                query words match the start of the words: par finds Paris, not aris
+    --elastic-url=<string>  Search in the catalog filled by info --elastic-url (url with index, e.g. http://localhost:9200/oms),
+                            in title, summary, casts, genres and file path, files are not read: search --elastic-url=<string> <query>
+                            (no path, the size, date, media and directory filters are not supported)
+    --archive-depth=<int>   Search in zip, tar, tar.gz, tgz files, and archives inside up to this depth, default 3 (0: don't open archives)
+    Files ignored by .gitignore or .omsignore are skipped
 ")
 }
 
@@ -271,17 +785,21 @@ search [options] <file_path|directory_path> <query>
 ///
 /// ```
 /// use oms::app::commands::search;
-/// use std::collections::HashMap;
+/// use std::collections::{BTreeMap, HashMap};
 /// 
 /// let args = vec!["oms".to_string(), "search".to_string(), "/home/me/text.txt".to_string(), "search term".to_string()];
 /// search::build_cmd(&args, HashMap::new());
 /// ```
 pub fn build_cmd(args: &Vec<String>, options: HashMap<String, String>) -> Result<Search> {
-    let file_path = get_args_parameter(
-        args,
-        args.len() - 2, // Get before last agruments
-        "\nread error: 'file_path' parameter required\n"
-    ).unwrap_or_default();
+    // search --elastic-url=<url> <query>: the catalog is searched on the server, there is no path
+    let file_path = match options.contains_key("elastic-url") {
+        true => "",
+        false => get_args_parameter(
+            args,
+            args.len() - 2, // Get before last agruments
+            "\nread error: 'file_path' parameter required\n"
+        ).unwrap_or_default(),
+    };
 
     let search_term = get_args_parameter(
         args,
@@ -0,0 +1,96 @@
+use std::{fs, io::Read, sync::mpsc::Sender};
+use colored::Colorize;
+use crate::helpers::{file::{archive::{archive_format, extract_to_temp, for_each_entry, ARCHIVE_PATH_SEPARATOR}, get_extension, magic}, interrupt};
+use super::{file_document, is_searchable, SearchOption, document::Document, result::SearchResult, stats::SearchStats};
+
+///
+/// cargo run -- search /home/solofo/deliveries.zip invoice
+///
+/// Files inside the archive are extracted in a temporary directory,
+/// then read by the handler of their type (text, pdf, office...).
+/// Results are reported as archive.zip!/inner/path.txt
+pub struct ArchiveSearch<'a> {
+    pub file_path: &'a String,
+    /// Path displayed in results, archive.zip!/inner.tar for a nested archive
+    pub display_path: &'a String,
+    /// zip, tar.gz or tar (see archive_format)
+    pub format: &'a str,
+    pub search_option: &'a SearchOption,
+    /// Nesting level of this archive, from 1
+    pub depth: usize,
+}
+
+impl<'a> ArchiveSearch<'a> {
+    /// Search the files inside, the archive itself is counted in stats by the caller
+    pub fn search(&self, tx: Sender<SearchResult>, stats: &SearchStats) {
+        self.documents(&mut |document| {
+            if !document.errors.is_empty() {
+                stats.fail(&document.file_path, document.errors.clone());
+            }
+            match document.search(self.search_option) {
+                Some(result) => {
+                    stats.matched();
+                    tx.send(result).unwrap_or_default();
+                },
+                None => stats.unmatched(&document.file_path),
+            }
+        });
+    }
+
+    /// Call callback with the document of each file inside the archive (and nested archives)
+    pub fn documents<F: FnMut(Document)>(&self, callback: &mut F) {
+        let entries = for_each_entry(self.file_path, self.format, |entry_path, content| {
+            self.entry_documents(entry_path, content, callback)
+        });
+        if let Err(err) = entries {
+            eprintln!("{}", format!("Warning: {}: {err}", self.display_path).yellow());
+        }
+    }
+
+    /// The type of the entry is detected once extracted, entries are extracted whatever the filters if they may be nested archives
+    fn entry_documents<F: FnMut(Document)>(&self, entry_path: &str, content: &mut dyn Read, callback: &mut F) {
+        // -q: a file matched (or not with -L), the other entries are not read
+        if self.search_option.quiet && interrupt::is_interrupted() {
+            return;
+        }
+        let display_path = format!("{}{ARCHIVE_PATH_SEPARATOR}{entry_path}", self.display_path);
+        let nested = self.depth < self.search_option.archive_depth;
+        let named_archive = archive_format(entry_path, &get_extension(entry_path).to_lowercase()).is_some();
+        if !nested && (named_archive || !is_searchable(entry_path, self.search_option)) {
+            return;
+        }
+
+        let temp_path = match extract_to_temp(entry_path, content) {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("{}", format!("Warning: {display_path}: {err}").yellow());
+                return;
+            },
+        };
+        let temp_file = temp_path.display().to_string();
+        let extension = magic::media_extension(&temp_file);
+
+        match archive_format(&temp_file, &extension) {
+            Some(format) if nested => ArchiveSearch {
+                file_path: &temp_file,
+                display_path: &display_path,
+                format,
+                search_option: self.search_option,
+                depth: self.depth + 1,
+            }.documents(callback),
+            Some(_) => (),
+            None if is_searchable(entry_path, self.search_option) => {
+                // Handlers report the temporary path, replaced by the path in the archive
+                if let Some(mut document) = file_document(&temp_file, &extension, self.search_option) {
+                    document.rename(&display_path);
+                    callback(document);
+                }
+            },
+            None => (),
+        }
+
+        if let Some(temp_dir) = temp_path.parent() {
+            let _ = fs::remove_dir_all(temp_dir);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{BTreeSet, VecDeque};
+use serde::{Deserialize, Serialize};
+use crate::helpers::{interrupt, query::{self, QueryMatch}};
+use super::{option::SearchOption, result::SearchResult, text_query_contains};
+
+/// Text extracted from a file by its search handler, independent of the query
+///
+/// Searched right after the extraction, or stored in the index (oms index) to be searched later
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Document {
+    pub file_path: String,
+    /// Media kind: text, markup, pdf, epub, office, mail, video, audio, image
+    pub kind: String,
+    pub sections: Vec<Section>,
+    /// Errors of the extractors, the sections hold what could be read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Searchable part of a document
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Section {
+    /// Metadata (File, Title, Casts...), found as a whole
+    Field { name: String, text: String },
+    /// Long text (office content) searched line by line, each text found is reported as a field
+    Content { name: String, text: String },
+    /// Lines of a text file
+    Lines { lines: Vec<String> },
+    /// Lines of a markup file (html, markdown...) under a heading, with their line number in the file
+    Markup { heading: String, lines: Vec<(usize, String)> },
+    Page { page: usize, text: String },
+    Chapter { chapter: usize, title: String, text: String },
+    /// Subtitle cue, timestamp as 01:02:03.500
+    Cue { source: String, timestamp: String, text: String },
+    /// Email message: header lines and body, hits are reported with the subject, sender and date
+    Message { message: usize, subject: String, from: String, date: String, text: String },
+}
+
+impl Document {
+    pub fn new(file_path: &str, kind: &str) -> Self {
+        Document {
+            file_path: file_path.to_string(),
+            kind: kind.to_string(),
+            sections: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn push_error(&mut self, error: &str) {
+        self.errors.push(error.to_string());
+    }
+
+    pub fn push_field(&mut self, name: &str, text: &str) {
+        self.sections.push(Section::Field {
+            name: name.to_string(),
+            text: text.to_string(),
+        });
+    }
+
+    /// Text of the first field with this name
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.sections.iter().find_map(|section| match section {
+            Section::Field { name: field, text } if field == name => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Search the query in all the sections, None if the document doesn't match
+    pub fn search(&self, search_option: &SearchOption) -> Option<SearchResult> {
+        let mut query_match = search_option.query.matcher();
+        let mut result = SearchResult::new(&self.file_path, &self.kind);
+        self.search_sections(&mut query_match, &mut result, search_option);
+        query_match.is_match().then_some(result)
+    }
+
+    /// Add the hits of the sections to result, the query is evaluated by the caller
+    pub fn search_sections(&self, query_match: &mut QueryMatch, result: &mut SearchResult, search_option: &SearchOption) {
+        let query = &search_option.query;
+        let context = (search_option.before_context, search_option.after_context);
+
+        for section in &self.sections {
+            if is_search_done(query_match, search_option) {
+                return;
+            }
+            match section {
+                Section::Field { name, text } => {
+                    if query_match.test(name, text) {
+                        result.push_field(name, text, query);
+                    }
+                },
+                Section::Content { name, text } => {
+                    for window in text_query_contains(text, name, query_match, (0, 0)).unwrap_or_default() {
+                        result.push_field(name, &window.text, query).text_start = window.start;
+                    }
+                },
+                Section::Lines { lines } => search_lines(lines.iter().cloned(), query_match, result, search_option),
+                Section::Markup { heading, lines } => search_markup(heading, lines, query_match, result, search_option),
+                Section::Page { page, text } => {
+                    let text_page = format!("Page {page}");
+                    for window in text_query_contains(text, &text_page, query_match, context).unwrap_or_default() {
+                        let hit = result.push_page(*page, &window.text, window.offsets, query);
+                        hit.line = window.line;
+                        hit.text_start = window.start;
+                        hit.context_before = window.context_before;
+                        hit.context_after = window.context_after;
+                    }
+                },
+                Section::Chapter { chapter, title, text } => {
+                    let text_chapter = format!("Chapter {chapter}");
+                    for window in text_query_contains(text, &text_chapter, query_match, context).unwrap_or_default() {
+                        let hit = result.push_chapter(*chapter, title, &window.text, window.offsets, query);
+                        hit.line = window.line;
+                        hit.text_start = window.start;
+                        hit.context_before = window.context_before;
+                        hit.context_after = window.context_after;
+                    }
+                },
+                Section::Message { message, subject, from, date, text } => {
+                    let text_message = format!("Message {message}");
+                    for window in text_query_contains(text, &text_message, query_match, context).unwrap_or_default() {
+                        let hit = result.push_message(*message, subject, &window.text, window.offsets, query);
+                        hit.from = from.clone();
+                        hit.date = date.clone();
+                        hit.line = window.line;
+                        hit.text_start = window.start;
+                        hit.context_before = window.context_before;
+                        hit.context_after = window.context_after;
+                    }
+                },
+                Section::Cue { source, timestamp, text } => {
+                    let offsets = query_match.find("Subtitle", text);
+                    if !offsets.is_empty() {
+                        result.push_cue(source, timestamp, text, offsets, query);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Normalized words of all the sections, as stored in the index
+    pub fn words(&self) -> BTreeSet<String> {
+        let mut words = BTreeSet::new();
+        for section in &self.sections {
+            match section {
+                Section::Field { text, .. }
+                | Section::Content { text, .. }
+                | Section::Page { text, .. }
+                | Section::Cue { text, .. } => words.extend(query::words(text)),
+                Section::Chapter { title, text, .. } => {
+                    words.extend(query::words(title));
+                    words.extend(query::words(text));
+                },
+                Section::Message { subject, from, date, text, .. } => {
+                    for text in [subject, from, date, text] {
+                        words.extend(query::words(text));
+                    }
+                },
+                Section::Lines { lines } => lines.iter().for_each(|line| words.extend(query::words(line))),
+                Section::Markup { heading, lines } => {
+                    words.extend(query::words(heading));
+                    lines.iter().for_each(|(_, line)| words.extend(query::words(line)));
+                },
+            }
+        }
+        words
+    }
+
+    /// Report the document with another path (file extracted from an archive)
+    pub fn rename(&mut self, file_path: &str) {
+        for section in self.sections.iter_mut() {
+            if let Section::Field { text, .. } = section {
+                *text = text.replace(&self.file_path, file_path);
+            }
+        }
+        self.file_path = file_path.to_string();
+    }
+}
+
+/// Lines containing the query, with the lines around that are not already found
+///
+/// Lines are searched as they come: only the lines before the current one (context)
+/// and the hits waiting for their lines after are kept, large files are not loaded
+pub fn search_lines<I: Iterator<Item = String>>(lines: I, query_match: &mut QueryMatch, result: &mut SearchResult, search_option: &SearchOption) {
+    let query = &search_option.query;
+    // Lines after the last hit, up to before_context
+    let mut before: VecDeque<String> = VecDeque::new();
+    // Index of the hits with the number of lines after still missing
+    let mut waiting: Vec<(usize, usize)> = vec![];
+    for (index, line) in lines.enumerate() {
+        if is_search_done(query_match, search_option) {
+            return;
+        }
+        for (hit, missing) in waiting.iter_mut() {
+            result.hits[*hit].context_after.push(line.clone());
+            *missing -= 1;
+        }
+        waiting.retain(|(_, missing)| *missing > 0);
+
+        let offsets = query_match.find("Line", &line);
+        if offsets.is_empty() {
+            before.push_back(line);
+            if before.len() > search_option.before_context {
+                before.pop_front();
+            }
+            continue;
+        }
+        let hit = result.push_line(index + 1, &line, offsets, query);
+        hit.context_before = before.drain(..).collect();
+        if search_option.after_context > 0 {
+            waiting.push((result.hits.len() - 1, search_option.after_context));
+        }
+    }
+}
+
+/// -q: the rest of the file is not searched once it is sure to match, or once the search is stopped (another file decided)
+pub fn is_search_done(query_match: &QueryMatch, search_option: &SearchOption) -> bool {
+    search_option.quiet && (query_match.is_certain_match() || interrupt::is_interrupted())
+}
+
+/// Markup lines containing the query, reported with the heading,
+/// the context is limited to the lines that follow each other in the file
+fn search_markup(heading: &str, lines: &[(usize, String)], query_match: &mut QueryMatch, result: &mut SearchResult, search_option: &SearchOption) {
+    let query = &search_option.query;
+    let mut context_start = 0;
+    for (index, (line, text)) in lines.iter().enumerate() {
+        let offsets = query_match.find("Line", text);
+        if offsets.is_empty() {
+            continue;
+        }
+        let before: Vec<String> = (context_start..index)
+            .rev()
+            .take(search_option.before_context)
+            .take_while(|&i| lines[i].0 + (index - i) == *line)
+            .map(|i| lines[i].1.clone())
+            .collect();
+        let after: Vec<String> = (index + 1..lines.len())
+            .take(search_option.after_context)
+            .take_while(|&i| lines[i].0 == *line + (i - index))
+            .map(|i| lines[i].1.clone())
+            .collect();
+        let hit = result.push_line(*line, text, offsets, query);
+        hit.field = heading.to_string();
+        hit.context_before = before.into_iter().rev().collect();
+        hit.context_after = after;
+        context_start = index + 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn document_search_lines() {
+        let mut document = Document::new("notes.txt", "text");
+        document.push_field("File", "notes.txt");
+        document.sections.push(Section::Lines {
+            lines: ["a", "foo", "b", "c", "foo bar", "d"].iter().map(|l| l.to_string()).collect(),
+        });
+        let mut search_option = SearchOption::new("foo".to_string());
+        search_option.set_context("1").unwrap();
+
+        let result = document.search(&search_option).unwrap();
+        let lines: Vec<(usize, &str, Vec<String>, Vec<String>)> = result.hits
+            .iter()
+            .map(|h| (h.line, h.text.as_str(), h.context_before.clone(), h.context_after.clone()))
+            .collect();
+        assert_eq!(vec![
+            (2, "foo", vec!["a".to_string()], vec!["b".to_string()]),
+            (5, "foo bar", vec!["c".to_string()], vec!["d".to_string()]),
+        ], lines);
+        assert!(document.search(&SearchOption::new("foo -bar".to_string())).is_none());
+        assert!(document.words().contains("notes"));
+
+        // -q: stop at the first hit, unless a negated term may still be found
+        search_option.set_quiet();
+        assert_eq!(1, document.search(&search_option).unwrap().hits.len());
+        let mut search_option = SearchOption::new("foo -bar".to_string());
+        search_option.set_quiet();
+        assert!(document.search(&search_option).is_none());
+    }
+
+    #[test]
+    fn document_search_long_line() {
+        let line = format!("{} needle {}", "a".repeat(300), "b".repeat(300));
+        let mut document = Document::new("book.pdf", "pdf");
+        document.sections.push(Section::Page { page: 1, text: line.clone() });
+        let result = document.search(&SearchOption::new("needle".to_string())).unwrap();
+        let hit = &result.hits[0];
+        let (start, end) = hit.offsets[0];
+        assert!(hit.text.len() < line.len());
+        assert_eq!("needle", &hit.text[start..end]);
+        assert_eq!("needle", &line[hit.text_start + start..hit.text_start + end]);
+    }
+}
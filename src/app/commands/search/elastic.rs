@@ -0,0 +1,156 @@
+use std::{collections::HashMap, io::{self, Error, ErrorKind}, sync::mpsc::Sender};
+use colored::Colorize;
+use serde_json::{json, Value};
+use crate::helpers::{file::get_file_name, interrupt};
+use super::{is_searchable, SearchOption, document::Document, result::SearchResult, stats::SearchStats};
+
+/// Catalog fields searched when the terms have no field prefix, the title weighs more
+const ELASTIC_FIELDS: [&str; 5] = ["title^3", "summary", "casts^2", "genres^2", "file_path"];
+
+/// Documents requested by page
+const ELASTIC_PAGE_SIZE: usize = 100;
+
+/// Documents that can be paged with from and size (default index.max_result_window of the server)
+const ELASTIC_MAX_HITS: usize = 10_000;
+
+/// Options of the files read, the catalog has no such information: rejected rather than ignored
+const UNSUPPORTED_OPTIONS: [&str; 13] = [
+    "index", "binary", "archive-depth", "max-depth", "hidden", "follow-symlinks",
+    "min-size", "max-size", "newer-than", "older-than", "min-duration", "codec", "resolution",
+];
+
+///
+/// cargo run -- search --elastic-url="http://nas:9200/oms" "dune -cast:lynch"
+///
+/// ## Features
+///
+/// * [x] Query sent to the server: a multi_match by term over title, summary, casts, genres and file_path
+/// * [x] Field prefixes, OR, negated terms and phrases of the query syntax, --fuzzy
+/// * [x] Hits read by pages of 100, up to 10000 (warning beyond)
+/// * [x] Hits highlighted in the catalog fields, files are not read (no need to mount the share)
+pub struct ElasticSearch<'a> {
+    pub search_option: &'a SearchOption,
+}
+
+impl<'a> ElasticSearch<'a> {
+    /// Send the documents found in the catalog of --elastic-url
+    pub fn search(&self, tx: Sender<SearchResult>, stats: &SearchStats) -> Result<(), io::Error> {
+        let Some(elastic) = &self.search_option.elastic else {
+            return Ok(());
+        };
+        let limit = match self.search_option.max_results {
+            0 => ELASTIC_MAX_HITS,
+            max_results => max_results.min(ELASTIC_MAX_HITS),
+        };
+        let mut from = 0;
+        while from < limit && !interrupt::is_interrupted() {
+            let size = ELASTIC_PAGE_SIZE.min(limit - from);
+            let (sources, total) = elastic.search(&self.query_body(from, size))?;
+            let count = sources.len();
+            for source in sources {
+                if interrupt::is_interrupted() {
+                    break;
+                }
+                let document = catalog_document(&source);
+                if !is_searchable(&document.file_path, self.search_option) {
+                    stats.skip();
+                    continue;
+                }
+                stats.found(source["file_size"].as_u64().unwrap_or_default());
+                stats.matched();
+                tx.send(self.catalog_result(&document)).unwrap_or_default();
+            }
+            from += count;
+            if count < size || from as u64 >= total {
+                break;
+            }
+            if from >= limit && self.search_option.max_results == 0 {
+                eprintln!("{}", format!("Warning: only the first {from} of the {total} documents found in the catalog are read").yellow());
+            }
+        }
+        Ok(())
+    }
+
+    /// Body of the _search request of a page
+    fn query_body(&self, from: usize, size: usize) -> Value {
+        json!({
+            "query": self.search_option.query.elastic_query(&ELASTIC_FIELDS),
+            "from": from,
+            "size": size,
+        })
+    }
+
+    /// Hits of the query in the catalog fields, the server may find the document with its own analysis
+    /// (stemming, synonyms): the title is reported then
+    fn catalog_result(&self, document: &Document) -> SearchResult {
+        document.search(self.search_option).unwrap_or_else(|| {
+            let mut result = SearchResult::new(&document.file_path, &document.kind);
+            let title = document.field("Title").unwrap_or_default();
+            result.push_field("Title", title, &self.search_option.query);
+            result
+        })
+    }
+}
+
+/// Error for the options of the files read (filters, walk, --index), not applied to the catalog
+pub fn check_options(options: &HashMap<String, String>) -> Result<(), io::Error> {
+    match UNSUPPORTED_OPTIONS.iter().find(|option| options.contains_key(**option)) {
+        Some(option) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("\nsearch error: --{option} is not supported with --elastic-url, the files are not read\n")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Document of a catalog entry (video, audio, pdf... result stored by info --elastic-url)
+fn catalog_document(source: &Value) -> Document {
+    let text = |name: &str| match &source[name] {
+        Value::Array(values) => values.iter().filter_map(Value::as_str).collect::<Vec<&str>>().join(", "),
+        value => value.as_str().unwrap_or_default().to_string(),
+    };
+    let file_path = text("file_path");
+    let mut document = Document::new(&file_path, source["file_type"].as_str().unwrap_or("video"));
+    document.push_field("File", &get_file_name(&file_path).to_lowercase());
+    for (field, name) in [("Title", "title"), ("Summary", "summary"), ("Genres", "genres"), ("Casts", "casts")] {
+        let value = text(name);
+        if !value.is_empty() {
+            document.push_field(field, &value);
+        }
+    }
+    document
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn elastic_catalog_document() {
+        let source = json!({
+            "title": "Dune",
+            "summary": "Paul Atreides arrives on Arrakis",
+            "genres": ["Science Fiction", "Adventure"],
+            "casts": ["Timothée Chalamet"],
+            "file_path": "/media/nas/films/Dune.2021.mkv",
+            "file_type": "video",
+            "file_size": 42,
+        });
+        let document = catalog_document(&source);
+        assert_eq!("video", document.kind);
+        assert_eq!(Some("Science Fiction, Adventure"), document.field("Genres"));
+
+        let hits = |search_term: &str| {
+            let search_option = SearchOption::new(search_term.to_string());
+            let result = ElasticSearch { search_option: &search_option }.catalog_result(&document);
+            result.hits.iter().map(|hit| (hit.field.clone(), hit.text.clone())).collect::<Vec<(String, String)>>()
+        };
+        assert_eq!(vec![("Casts".to_string(), "Timothée Chalamet".to_string())], hits("chalamet"));
+        // Found by the server only (stemming): reported by its title
+        assert_eq!(vec![("Title".to_string(), "Dune".to_string())], hits("arrival"));
+
+        let options = |names: &[&str]| names.iter().map(|name| (name.to_string(), String::new())).collect::<HashMap<String, String>>();
+        assert!(check_options(&options(&["elastic-url", "e", "max-results"])).is_ok());
+        assert!(check_options(&options(&["elastic-url", "min-size"])).unwrap_err().to_string().contains("--min-size"));
+    }
+}
@@ -0,0 +1,41 @@
+use crate::helpers::media::epub::{get_epub_chapters, get_epub_result};
+use super::{SearchOption, document::{Document, Section}};
+
+
+///
+/// cargo run -- search /home/solofo/Documents/books nautilus
+///
+/// ## Features
+///
+/// * [x] Search in metadata (title, author, subject)
+/// * [x] Search in chapters, following the spine order
+pub struct EpubSearch<'a> {
+    pub file_path: &'a String,
+    pub search_option: &'a SearchOption,
+}
+
+impl<'a> EpubSearch<'a> {
+    pub fn document(&self) -> Document {
+        let mut document = Document::new(self.file_path, "epub");
+
+        match get_epub_result(&String::new(), self.file_path) {
+            Ok(epub) => for (item, text) in epub.fields() {
+                document.push_field(item, &text);
+            },
+            Err(err) => document.push_error(&err.to_string()),
+        }
+
+        let chapters = get_epub_chapters(self.file_path).unwrap_or_else(|err| {
+            document.push_error(&err.to_string());
+            vec![]
+        });
+        for (index, chapter) in chapters.into_iter().enumerate() {
+            document.sections.push(Section::Chapter {
+                chapter: index + 1,
+                title: chapter.title,
+                text: chapter.content,
+            });
+        }
+        document
+    }
+}
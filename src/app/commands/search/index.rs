@@ -0,0 +1,215 @@
+use std::{collections::{BTreeMap, BTreeSet}, fs, ops::Bound, io::{Error, ErrorKind}, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+use crate::helpers::{cache, file::archive::ARCHIVE_PATH_SEPARATOR, query::Query};
+use super::document::Document;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Directory of the index, in the cache path
+const INDEX_DIR: &str = ".index";
+
+/// Inverted index of the documents extracted by the search handlers, stored in the cache path
+///
+/// `.index/index.json` holds the indexed files and the words of their documents,
+/// each document is stored in `.index/documents/<id>.json` and read only if it may match the query
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SearchIndex {
+    /// Indexed files by absolute path
+    files: BTreeMap<String, IndexedFile>,
+    /// Path of each document: the file, or archive.zip!/inner.txt for a file inside an archive
+    documents: BTreeMap<usize, String>,
+    /// Normalized word -> ids of the documents containing it
+    words: BTreeMap<String, BTreeSet<usize>>,
+    next_id: usize,
+    /// Documents removed since loaded, their words are cleaned on save
+    #[serde(skip)]
+    removed: BTreeSet<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct IndexedFile {
+    /// Nanoseconds since epoch
+    modification_time: u64,
+    size: u64,
+    /// Ids of the documents of the file (several for an archive)
+    documents: Vec<usize>,
+}
+
+impl SearchIndex {
+    /// Load the index from the cache path, empty if not built yet
+    pub fn load() -> Result<Self> {
+        let index_path = index_dir()?.join("index.json");
+        match fs::read_to_string(&index_path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|err| Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {err}", index_path.display())
+            )),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(SearchIndex::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the index, replacing the previous one at once
+    pub fn save(&mut self) -> Result<()> {
+        if !self.removed.is_empty() {
+            let removed = std::mem::take(&mut self.removed);
+            self.words.retain(|_, ids| {
+                ids.retain(|id| !removed.contains(id));
+                !ids.is_empty()
+            });
+        }
+        let dir = index_dir()?;
+        let temp_path = dir.join("index.json.tmp");
+        fs::write(&temp_path, serde_json::to_string(self)?)?;
+        fs::rename(temp_path, dir.join("index.json"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Check if the file is indexed with the same modification time and size
+    pub fn is_up_to_date(&self, file_path: &str, modification_time: u64, size: u64) -> bool {
+        self.files
+            .get(file_path)
+            .is_some_and(|f| f.modification_time == modification_time && f.size == size)
+    }
+
+    /// Indexed files in a directory (or the file itself)
+    pub fn files_in(&self, path: &str) -> Vec<String> {
+        self.files
+            .keys()
+            .filter(|file_path| Path::new(file_path).starts_with(path))
+            .cloned()
+            .collect()
+    }
+
+    /// Replace the documents of a file
+    pub fn insert(&mut self, file_path: &str, modification_time: u64, size: u64, documents: Vec<Document>) -> Result<()> {
+        self.remove(file_path);
+        let documents_dir = index_dir()?.join("documents");
+        fs::create_dir_all(&documents_dir)?;
+
+        let mut ids = vec![];
+        for document in documents {
+            let id = self.next_id;
+            self.next_id += 1;
+            fs::write(documents_dir.join(format!("{id}.json")), serde_json::to_string(&document)?)?;
+            for word in document.words() {
+                self.words.entry(word).or_default().insert(id);
+            }
+            self.documents.insert(id, document.file_path);
+            ids.push(id);
+        }
+        self.files.insert(file_path.to_string(), IndexedFile {
+            modification_time,
+            size,
+            documents: ids,
+        });
+        Ok(())
+    }
+
+    /// Remove a file and its documents
+    pub fn remove(&mut self, file_path: &str) {
+        let Some(indexed_file) = self.files.remove(file_path) else {
+            return;
+        };
+        let documents_dir = index_dir().map(|dir| dir.join("documents"));
+        for id in indexed_file.documents {
+            self.documents.remove(&id);
+            if let Ok(dir) = &documents_dir {
+                let _ = fs::remove_file(dir.join(format!("{id}.json")));
+            }
+            self.removed.insert(id);
+        }
+    }
+
+    /// Id and path of the documents in path which may match the query:
+    /// they contain words starting with the words of the terms of at least one OR clause
+    ///
+    /// Field prefixes and negated terms are checked when searching the documents
+    pub fn candidates(&self, query: &Query, path: &str) -> Vec<(usize, String)> {
+        let clauses = query.required_words();
+        let ids: BTreeSet<usize> = if query.is_fuzzy() || clauses.iter().any(|words| words.is_empty()) {
+            self.documents.keys().copied().collect()
+        } else {
+            clauses.iter().flat_map(|words| self.clause_documents(words)).collect()
+        };
+
+        ids.into_iter()
+            .filter_map(|id| self.documents.get(&id).map(|document_path| (id, document_path)))
+            .filter(|(_, document_path)| {
+                let file_path = document_path.split(ARCHIVE_PATH_SEPARATOR).next().unwrap_or_default();
+                Path::new(file_path).starts_with(path)
+            })
+            .map(|(id, document_path)| (id, document_path.clone()))
+            .collect()
+    }
+
+    pub fn load_document(&self, id: usize) -> Result<Document> {
+        let document_path = index_dir()?.join("documents").join(format!("{id}.json"));
+        let content = fs::read_to_string(document_path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Documents containing all the words, a term word may be the start of a document word
+    /// (read from the sorted words, from the term word to the last word starting with it)
+    fn clause_documents(&self, words: &[String]) -> BTreeSet<usize> {
+        let mut ids: Option<BTreeSet<usize>> = None;
+        for word in words {
+            let word_ids: BTreeSet<usize> = self.words
+                .range::<str, _>((Bound::Included(word.as_str()), Bound::Unbounded))
+                .take_while(|(indexed_word, _)| indexed_word.starts_with(word.as_str()))
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect();
+            ids = Some(match ids {
+                Some(ids) => ids.intersection(&word_ids).copied().collect(),
+                None => word_ids,
+            });
+        }
+        ids.unwrap_or_default()
+    }
+}
+
+/// Index directory in the cache path, required to build or read the index
+fn index_dir() -> Result<PathBuf> {
+    let base_path = cache::get_base_path();
+    if base_path.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "\nindex error: --cache-path=<dir> is required to store the index\n"
+        ));
+    }
+    let dir = Path::new(&base_path).join(INDEX_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::commands::search::document::Section;
+
+    #[test]
+    fn index_candidates() {
+        let mut index = SearchIndex::default();
+        for (id, (path, text)) in [("/docs/a.txt", "Un été à Paris"), ("/docs/b.zip!/b.txt", "Paris by night"), ("/other/c.txt", "été")].iter().enumerate() {
+            let mut document = Document::new(path, "text");
+            document.sections.push(Section::Lines { lines: vec![text.to_string()] });
+            for word in document.words() {
+                index.words.entry(word).or_default().insert(id);
+            }
+            index.documents.insert(id, path.to_string());
+        }
+
+        let paths = |query: &str, path: &str| -> Vec<String> {
+            index.candidates(&Query::from(query), path).into_iter().map(|(_, p)| p).collect()
+        };
+        assert_eq!(vec!["/docs/a.txt"], paths("ete par", "/docs"));
+        assert_eq!(vec!["/docs/a.txt", "/docs/b.zip!/b.txt"], paths("paris", "/docs"));
+        assert_eq!(vec!["/docs/b.zip!/b.txt"], paths("ete OR night", "/docs/b.zip"));
+        assert_eq!(3, paths("-foo", "/").len());
+        assert!(paths("london", "/").is_empty());
+        assert!(paths("aris", "/").is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fs, io};
+use sha256::digest;
+use crate::helpers::{file::{self, archive::extract_to_temp, get_file_name, magic}, media::{mail::{read_messages, MailAttachment}, office::get_office_content, pdf::get_pdf_pages}};
+use super::{SearchOption, document::{is_search_done, Document, Section}, result::SearchResult};
+
+///
+/// cargo run -- search /home/me/Mail/projects.mbox budget
+///
+/// ## Features
+///
+/// * [x] eml (one message) and mbox files, messages searched as they are read
+/// * [x] Search in the headers (From, To, Cc, Subject, Date) and the text parts
+/// * [x] Search in the attachments: text, pdf and office files
+pub struct MailSearch<'a> {
+    pub file_path: &'a String,
+    pub search_option: &'a SearchOption,
+}
+
+impl<'a> MailSearch<'a> {
+    pub fn document(&self) -> Document {
+        let mut document = self.file_document();
+        let mut sections = vec![];
+        self.messages(&mut document.errors, |section| {
+            sections.push(section);
+            true
+        });
+        document.sections.extend(sections);
+        document
+    }
+
+    /// Same as searching the document, but each message is searched once parsed:
+    /// only the hits are kept in memory (large mbox)
+    ///
+    /// Returns the result if the file matches, and the errors of the reader and of the attachments
+    pub fn search(&self) -> (Option<SearchResult>, Vec<String>) {
+        let mut document = self.file_document();
+        let mut query_match = self.search_option.query.matcher();
+        let mut result = SearchResult::new(self.file_path, &document.kind);
+        document.search_sections(&mut query_match, &mut result, self.search_option);
+        let mut message = Document::new(self.file_path, &document.kind);
+        self.messages(&mut document.errors, |section| {
+            message.sections = vec![section];
+            message.search_sections(&mut query_match, &mut result, self.search_option);
+            !is_search_done(&query_match, self.search_option)
+        });
+        (query_match.is_match().then_some(result), document.errors)
+    }
+
+    /// Document with the file name
+    fn file_document(&self) -> Document {
+        let mut document = Document::new(self.file_path, "mail");
+        document.push_field("File", &get_file_name(self.file_path).to_lowercase());
+        document
+    }
+
+    /// Call callback with the section of each message (headers, body and attachments), in the order of the file,
+    /// until it returns false
+    fn messages<F: FnMut(Section) -> bool>(&self, errors: &mut Vec<String>, mut callback: F) {
+        let messages = match read_messages(self.file_path) {
+            Ok(messages) => messages,
+            Err(err) => {
+                errors.push(err.to_string());
+                return;
+            },
+        };
+        for (index, message) in messages.enumerate() {
+            let message = match message {
+                Ok(message) => message,
+                Err(err) => {
+                    errors.push(err.to_string());
+                    return;
+                },
+            };
+            let mut text = format!("{}\n\n{}\n", message.header_lines(), message.text);
+            for attachment in &message.attachments {
+                match attachment_text(attachment) {
+                    Ok(Some(content)) => text.push_str(&format!("\nAttachment: {}\n{content}\n", attachment.name)),
+                    Ok(None) => (),
+                    Err(err) => errors.push(format!("{}: {err}", attachment.name)),
+                }
+            }
+            let more = callback(Section::Message {
+                message: index + 1,
+                subject: message.subject,
+                from: message.from,
+                date: message.date,
+                text,
+            });
+            if !more {
+                return;
+            }
+        }
+    }
+}
+
+/// Text of a pdf or office attachment, extracted from a temporary copy, None for the other files
+///
+/// The type is read from the content, documents sent with a generic name (attachment.bin...) are found too
+fn attachment_text(attachment: &MailAttachment) -> io::Result<Option<String>> {
+    let extension = file::get_extension(&attachment.name).to_lowercase();
+    let is_document = matches!(magic::sniff(&attachment.content), Some("pdf" | "zip" | "ole"))
+        || file::PDF_EXTENSIONS.contains(&extension.as_str())
+        || file::MS_EXTENSIONS.contains(&extension.as_str());
+    if !is_document {
+        return Ok(None);
+    }
+    let temp_path = extract_to_temp(&attachment.name, &mut attachment.content.as_slice())?;
+    let temp_file = temp_path.display().to_string();
+    let extension = magic::media_extension(&temp_file);
+    let text = if file::PDF_EXTENSIONS.contains(&extension.as_str()) {
+        Ok(Some(get_pdf_pages(&temp_file, &digest(attachment.content.as_slice())).join("\n")))
+    } else if file::MS_EXTENSIONS.contains(&extension.as_str()) {
+        get_office_content(&temp_file, &extension).map(Some)
+    } else {
+        Ok(None)
+    };
+    if let Some(temp_dir) = temp_path.parent() {
+        let _ = fs::remove_dir_all(temp_dir);
+    }
+    text
+}
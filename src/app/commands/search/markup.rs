@@ -0,0 +1,33 @@
+use crate::helpers::{file::get_file_name, media::markup::read_markup};
+use super::{SearchOption, document::{Document, Section}};
+
+///
+/// cargo run -- search ./wiki budget
+///
+/// ## Features
+///
+/// * [x] html, xml, markdown and reStructuredText files
+/// * [x] Search in the text only: tags, attributes, scripts and styles are ignored, entities are decoded
+/// * [x] Hits reported with their line and the nearest heading
+pub struct MarkupSearch<'a> {
+    pub file_path: &'a String,
+    pub search_option: &'a SearchOption,
+}
+
+impl<'a> MarkupSearch<'a> {
+    pub fn document(&self) -> Document {
+        let mut document = Document::new(self.file_path, "markup");
+        document.push_field("File", &get_file_name(self.file_path));
+
+        match read_markup(self.file_path) {
+            Ok(sections) => for section in sections {
+                document.sections.push(Section::Markup {
+                    heading: section.heading,
+                    lines: section.lines,
+                });
+            },
+            Err(err) => document.push_error(&err.to_string()),
+        }
+        document
+    }
+}
@@ -1,34 +1,85 @@
 use std::{io::{Error, ErrorKind}, cmp::max};
-use crate::app::commands::OPTION_SEPARATOR;
+use crate::{app::commands::OPTION_SEPARATOR, helpers::{db::elastic::Elastic, file::{filter::FilterOption, pattern::{patterns_from, FilePattern}, walk::WalkOption}, query::Query}};
 
 type Result<T> = std::result::Result<T, std::io::Error>;
 
 
 pub struct SearchOption {
     pub search_term: String,
+    pub query: Query,
 
     pub display: String,
+    pub format: String,
+    /// -c: number of hits by file
+    pub count: bool,
+    /// -l (or --display=file-only): only the paths of the files matched
+    pub files_with_matches: bool,
+    /// -L: only the paths of the files searched without match
+    pub files_without_match: bool,
+    /// -q: no output, the search stops at the first file matched (exit code only)
+    pub quiet: bool,
+    pub before_context: usize,
+    pub after_context: usize,
     pub thread: usize,
+    pub walk: WalkOption,
+    pub filter: FilterOption,
+    /// Max depth of nested archives to search in, 0 to not open archives
+    pub archive_depth: usize,
+    /// Search in the index built by the index command instead of the files
+    pub index: bool,
+    /// Search in the catalog filled by info --elastic-url instead of the files
+    pub elastic: Option<Elastic>,
 
     pub extensions: Vec<String>,
     pub exclude_extensions: Vec<String>,
 
-    pub files: Vec<String>,
-    pub exclude_files: Vec<String>,
+    /// Glob or regex patterns (-f, --exclude-files) of the file names or paths relative to base_path
+    pub files: Vec<FilePattern>,
+    pub exclude_files: Vec<FilePattern>,
+    /// Searched directory
+    pub base_path: String,
     pub provider: String,
+    /// Binary text files: skip (default), name-only (search only the file name) or text
+    pub binary: String,
+
+    /// relevance, path (default), mtime (newest first) or size (largest first),
+    /// empty if not given: by path, in the order of the server with --elastic-url
+    pub sort: String,
+    /// Max number of files displayed, 0 for no limit
+    pub max_results: usize,
+    /// Max number of hits displayed by file, 0 for no limit
+    pub max_per_file: usize,
 }
 
 impl SearchOption {
     pub fn new(search_term: String) -> Self {
         SearchOption {
+            query: Query::from(&search_term),
             search_term: search_term,
             display: String::from("all"),
+            format: String::from("text"),
+            count: false,
+            files_with_matches: false,
+            files_without_match: false,
+            quiet: false,
+            before_context: 0,
+            after_context: 0,
             thread: max(1, num_cpus::get() - 1),
+            walk: WalkOption::new(),
+            filter: FilterOption::new(),
+            archive_depth: 3,
+            index: false,
+            elastic: None,
             extensions: vec![],
             exclude_extensions: vec![],
             files: vec![],
             exclude_files: vec![],
+            base_path: String::new(),
             provider: String::from("local"),
+            binary: String::from("skip"),
+            sort: String::new(),
+            max_results: 0,
+            max_per_file: 0,
         }
     }
     
@@ -58,10 +109,24 @@ impl SearchOption {
         }
     }
 
+    /// --fuzzy: default max distance 2, --fuzzy=<int> otherwise
+    pub fn set_fuzzy(&mut self, value: &str) -> Result<()> {
+        let distance = match value {
+            "" => 2,
+            value => value.parse::<usize>().map_err(|_| Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid value for fuzzy"
+            ))?,
+        };
+        self.query.set_fuzzy(distance);
+        Ok(())
+    }
+
     pub fn set_display(&mut self, value: &String) -> Result<()> {
         match value.as_str() {
             "file-only" | "debug" => {
                 self.display = value.clone();
+                self.files_with_matches = value == "file-only";
                 Ok(())
             },
             _ => Err(Error::new(
@@ -71,6 +136,117 @@ impl SearchOption {
         }
     }
 
+    pub fn set_format(&mut self, value: &str) -> Result<()> {
+        match value {
+            "text" | "json" | "ndjson" | "grep" => {
+                self.format = value.to_string();
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::NotFound, 
+                "Unknown value for format"
+            ))
+        }
+    }
+
+    pub fn set_binary(&mut self, value: &str) -> Result<()> {
+        match value {
+            "skip" | "text" | "name-only" => {
+                self.binary = value.to_string();
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                "Unknown value for binary"
+            ))
+        }
+    }
+
+    pub fn set_sort(&mut self, value: &str) -> Result<()> {
+        match value {
+            "relevance" | "path" | "mtime" | "size" => {
+                self.sort = value.to_string();
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                "Unknown value for sort"
+            ))
+        }
+    }
+
+    pub fn set_max_results(&mut self, value: &str) -> Result<()> {
+        self.max_results = parse_limit(value, "max-results")?;
+        Ok(())
+    }
+
+    pub fn set_max_per_file(&mut self, value: &str) -> Result<()> {
+        self.max_per_file = parse_limit(value, "max-per-file")?;
+        Ok(())
+    }
+
+    pub fn set_before_context(&mut self, value: &str) -> Result<()> {
+        self.before_context = parse_context(value)?;
+        Ok(())
+    }
+
+    pub fn set_after_context(&mut self, value: &str) -> Result<()> {
+        self.after_context = parse_context(value)?;
+        Ok(())
+    }
+
+    pub fn set_context(&mut self, value: &str) -> Result<()> {
+        self.set_before_context(value)?;
+        self.set_after_context(value)
+    }
+
+    pub fn has_context(&self) -> bool {
+        self.before_context > 0 || self.after_context > 0
+    }
+
+    pub fn set_archive_depth(&mut self, value: &str) -> Result<()> {
+        match value.parse::<usize>() {
+            Ok(v) => {
+                self.archive_depth = v;
+                Ok(())
+            },
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                "Invalid value for archive-depth"
+            ))
+        }
+    }
+
+    pub fn set_count(&mut self) {
+        self.count = true;
+    }
+
+    pub fn set_files_with_matches(&mut self) {
+        self.files_with_matches = true;
+    }
+
+    pub fn set_files_without_match(&mut self) {
+        self.files_without_match = true;
+    }
+
+    pub fn set_quiet(&mut self) {
+        self.quiet = true;
+    }
+
+    /// Stats summary on stderr, not with the outputs read by scripts (-l, -L, -c, -q, json, ndjson)
+    pub fn has_summary(&self) -> bool {
+        !(self.count || self.files_with_matches || self.files_without_match || self.quiet || matches!(self.format.as_str(), "json" | "ndjson"))
+    }
+
+    pub fn set_index(&mut self) {
+        self.index = true;
+    }
+
+    pub fn set_elastic(&mut self, value: &String) -> Result<()> {
+        self.elastic = Some(Elastic::new(value)?);
+        Ok(())
+    }
+
     pub fn extensions_from(&mut self, value: &String) -> Result<()> {
         self.extensions = value.split(OPTION_SEPARATOR).map(|s| s.to_lowercase().to_string()).collect();
         Ok(())
@@ -82,12 +258,12 @@ impl SearchOption {
     }
 
     pub fn files_from(&mut self, value: &String) -> Result<()> {
-        self.files = value.split(OPTION_SEPARATOR).map(|s| s.to_lowercase().to_string()).collect();
+        self.files = patterns_from(value, OPTION_SEPARATOR)?;
         Ok(())
     }
 
     pub fn exclude_files_from(&mut self, value: &String) -> Result<()> {
-        self.exclude_files = value.split(OPTION_SEPARATOR).map(|s| s.to_lowercase().to_string()).collect();
+        self.exclude_files = patterns_from(value, OPTION_SEPARATOR)?;
         Ok(())
     }
     
@@ -108,24 +284,44 @@ impl SearchOption {
         self.exclude_extensions.contains(extension)
     }
 
-    pub fn has_file(&self, file_name: &String) -> bool {
+    pub fn has_file(&self, file_path: &str) -> bool {
         if self.files.len() == 0 {
             return true;
         }
-        if file_name.is_empty() {
+        if file_path.is_empty() {
             return true;
         }
-        self.files.contains(file_name)
+        self.files.iter().any(|pattern| pattern.is_match(file_path, &self.base_path))
     }
 
-    pub fn is_file_excluded(&self, file_name: &String) -> bool {
+    pub fn is_file_excluded(&self, file_path: &str) -> bool {
         if self.exclude_files.len() == 0 {
             return false;
         }
-        if file_name.is_empty() {
+        if file_path.is_empty() {
             return false;
         }
-        self.exclude_files.contains(file_name)
+        self.exclude_files.iter().any(|pattern| pattern.is_match(file_path, &self.base_path))
+    }
+}
+
+fn parse_context(value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(v) => Ok(v),
+        _ => Err(Error::new(
+            ErrorKind::NotFound, 
+            "Invalid value for context"
+        ))
+    }
+}
+
+fn parse_limit(value: &str, option: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(v) if v > 0 => Ok(v),
+        _ => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("Invalid value for {option}")
+        ))
     }
 }
 
@@ -133,13 +329,31 @@ impl Clone for SearchOption {
     fn clone(&self) -> Self {
         SearchOption { 
             search_term: self.search_term.clone(),
+            query: self.query.clone(),
             display: self.display.clone(),
+            format: self.format.clone(),
+            count: self.count,
+            files_with_matches: self.files_with_matches,
+            files_without_match: self.files_without_match,
+            quiet: self.quiet,
+            before_context: self.before_context,
+            after_context: self.after_context,
             thread: self.thread,
+            walk: self.walk.clone(),
+            filter: self.filter.clone(),
+            archive_depth: self.archive_depth,
+            index: self.index,
+            elastic: self.elastic.clone(),
             extensions: self.extensions.clone(),
             exclude_extensions: self.exclude_extensions.clone(),
             files: self.files.clone(),
             exclude_files: self.exclude_files.clone(),
+            base_path: self.base_path.clone(),
             provider: self.provider.clone(),
+            binary: self.binary.clone(),
+            sort: self.sort.clone(),
+            max_results: self.max_results,
+            max_per_file: self.max_per_file,
         }
     }
 }
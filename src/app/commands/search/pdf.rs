@@ -1,6 +1,5 @@
-use std::sync::mpsc::Sender;
-use crate::helpers::{command, media::pdf::{get_pdf_result, content::PdfContent}};
-use super::{format_file_display, format_line_found, SearchOption, text_reg_contains};
+use crate::helpers::{file, media::pdf::{get_pdf_pages, get_pdf_result, PAGE_SEPARATOR}};
+use super::{SearchOption, document::{Document, Section}};
 
 
 ///
@@ -13,75 +12,33 @@ use super::{format_file_display, format_line_found, SearchOption, text_reg_conta
 /// ## Features
 /// 
 /// * [x] Search in metadata (title, author, keywords...)
-/// * [ ] Search in content
+/// * [x] Search in content
+/// * [x] Search in scanned pages (OCR with pdftoppm and tesseract)
 /// * [ ] Search in summary?
 pub struct PdfSearch<'a> {
     pub file_path: &'a String,
-    pub search_term: &'a String,
     pub search_option: &'a SearchOption,
 }
 
 impl<'a> PdfSearch<'a> {
-    fn loop_content<I>(&self, contents: I, found: &mut Vec<(String, String)>)
-    where 
-        I: Iterator<Item = String>
-    {
-        for (page, content) in contents.enumerate() {
-            if self.skip_file(&found) {
-                break;
-            }
-            match text_reg_contains(&content, &self.search_term) {
-                None => (),
-                Some(results) => {
-                    for line in results {
-                        if self.skip_file(&found) {
-                            break;
-                        }
-                        let text_page = "Page ".to_string() + &page.to_string();
-                        found.push((text_page, line));
-                    }
-                }
-            }
-        }
-    }
-
-    pub fn search(&self, tx: Sender<String>) {
-        let mut result = String::new();
-        let mut found: Vec<(String, String)> = vec![];
-
-        if let Ok(pdf) = get_pdf_result(&String::new(), self.file_path) {
-            let search_results = pdf.search(self.search_term);
-            if search_results.len() > 0 {
-                search_results.iter().for_each(|(item, text)| {
-                    found.push((item.to_string(), text.to_string()));
-                });
-            }
-        }
-        // pdftotext is slower?
-        let content = command::exec("___pdftotext", ["-layout", &self.file_path, "-"]);
-        if !content.is_empty() {
-            self.loop_content(
-                content.lines().map(|l|l.to_string()),
-                &mut found
-            );
-        } else {
-            let content = PdfContent::new(&self.file_path);
-            self.loop_content(content, &mut found);
-        }
-
-        if found.len() > 0 {
-            result.push_str(&format_file_display(&self.file_path));
-            found.iter().for_each(|(item, text)| {
-                result.push_str(&format_line_found(&item.to_string(), &text, &self.search_option));
-            });
-        }
+    pub fn document(&self) -> Document {
+        let mut document = Document::new(self.file_path, "pdf");
 
-        if !result.is_empty() {
-            tx.send(result).unwrap_or_default();
+        let pages: Vec<String> = match get_pdf_result(&String::new(), self.file_path, true) {
+            Ok(pdf) => {
+                for (item, text) in pdf.fields() {
+                    document.push_field(item, &text);
+                }
+                pdf.content.split(PAGE_SEPARATOR).map(|p| p.to_string()).collect()
+            },
+            Err(err) => {
+                document.push_error(&err.to_string());
+                get_pdf_pages(self.file_path, &file::sha256(self.file_path).unwrap_or(self.file_path.clone()))
+            },
+        };
+        for (page, text) in pages.into_iter().enumerate() {
+            document.sections.push(Section::Page { page: page + 1, text });
         }
+        document
     }    
-
-    fn skip_file(&self, found: &Vec<(String, String)>) -> bool {
-        self.search_option.display == "file-only" && found.len() > 0
-    }
 }
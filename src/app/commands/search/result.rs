@@ -0,0 +1,171 @@
+use serde::Serialize;
+use crate::helpers::query::Query;
+
+/// A file matching the search query
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub file_path: String,
+    /// Media kind: text, markup, pdf, epub, office, mail, video, audio, image
+    pub kind: String,
+    pub hits: Vec<SearchHit>,
+}
+
+/// Text found in a file
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub file_path: String,
+    pub kind: String,
+    /// line, page, chapter, message, subtitle or field (file name, title, casts...)
+    pub location: String,
+    /// Line number from 1 (in the page for a pdf), 0 if the location is not a line
+    pub line: usize,
+    /// Page (chapter or message) number from 1, 0 if the location is not a page
+    pub page: usize,
+    /// Field name (File, Title, Casts...), chapter title, message subject, subtitle source
+    /// or heading of a markup line, empty otherwise
+    pub field: String,
+    /// Sender and date of the message, empty if the location is not a message
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub from: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub date: String,
+    /// Start time of the subtitle cue (01:02:03.500), empty if the location is not a subtitle
+    pub timestamp: String,
+    /// Line or field found, only the part around the terms for the long lines of pdf, epub, mail and office files
+    pub text: String,
+    /// Byte offset of text in its line (0 when text is the whole line)
+    pub text_start: usize,
+    /// Byte ranges of the query terms in text, add text_start for the ranges in the line
+    pub offsets: Vec<(usize, usize)>,
+    /// Similarity with the query terms, 1 for exact match
+    pub score: f32,
+    /// Lines before and after the hit (-B, -A options)
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+impl SearchResult {
+    pub fn new(file_path: &str, kind: &str) -> Self {
+        SearchResult {
+            file_path: file_path.to_string(),
+            kind: kind.to_string(),
+            hits: vec![],
+        }
+    }
+
+    pub fn push_line(&mut self, line: usize, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("line", text, offsets, query);
+        hit.line = line;
+        self.push(hit)
+    }
+
+    pub fn push_page(&mut self, page: usize, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("page", text, offsets, query);
+        hit.page = page;
+        self.push(hit)
+    }
+
+    pub fn push_chapter(&mut self, chapter: usize, title: &str, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("chapter", text, offsets, query);
+        hit.page = chapter;
+        hit.field = title.to_string();
+        self.push(hit)
+    }
+
+    pub fn push_message(&mut self, message: usize, subject: &str, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("message", text, offsets, query);
+        hit.page = message;
+        hit.field = subject.to_string();
+        self.push(hit)
+    }
+
+    pub fn push_cue(&mut self, source: &str, timestamp: &str, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("subtitle", text, offsets, query);
+        hit.field = source.to_string();
+        hit.timestamp = timestamp.to_string();
+        self.push(hit)
+    }
+
+    pub fn push_field(&mut self, field: &str, text: &str, query: &Query) -> &mut SearchHit {
+        let mut hit = self.new_hit("field", text, query.find(text), query);
+        hit.field = field.to_string();
+        self.push(hit)
+    }
+
+    /// Relevance of the file for the query,
+    /// terms found in the title and metadata weigh more than many occurrences in the content
+    pub fn relevance(&self) -> f32 {
+        let mut metadata = 0.;
+        let mut content = 0.;
+        for hit in &self.hits {
+            match (hit.location.as_str(), hit.field.as_str()) {
+                ("field", "Title") => metadata += 4. * hit.score,
+                ("field", "File") => metadata += 2. * hit.score,
+                ("field", "Content") => content += hit.score,
+                ("field", _) => metadata += 3. * hit.score,
+                _ => content += hit.score,
+            }
+        }
+        metadata + (1. + content).ln()
+    }
+
+    fn push(&mut self, hit: SearchHit) -> &mut SearchHit {
+        self.hits.push(hit);
+        let last = self.hits.len() - 1;
+        &mut self.hits[last]
+    }
+
+    fn new_hit(&self, location: &str, text: &str, offsets: Vec<(usize, usize)>, query: &Query) -> SearchHit {
+        SearchHit {
+            file_path: self.file_path.clone(),
+            kind: self.kind.clone(),
+            location: location.to_string(),
+            line: 0,
+            page: 0,
+            field: String::new(),
+            from: String::new(),
+            date: String::new(),
+            timestamp: String::new(),
+            text: text.to_string(),
+            text_start: 0,
+            offsets,
+            score: query.similarity(text),
+            context_before: vec![],
+            context_after: vec![],
+        }
+    }
+}
+
+impl SearchHit {
+    /// Label of the location: line number (heading), Page x, Chapter x (title), subject (sender, date),
+    /// 01:02:03.500 (subtitle), field name
+    pub fn item(&self) -> String {
+        match self.location.as_str() {
+            "line" if self.field.is_empty() => self.line.to_string(),
+            "line" => format!("{} ({})", self.line, self.field),
+            "page" => format!("Page {}", self.page),
+            "chapter" => format!("Chapter {} ({})", self.page, self.field),
+            "message" => format!("{} ({}, {})", self.field, self.from, self.date),
+            "subtitle" => format!("{} ({})", self.timestamp, self.field),
+            _ => self.field.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn result_relevance() {
+        let query = Query::from("dune");
+        let mut title = SearchResult::new("dune.epub", "epub");
+        title.push_field("Title", "Dune", &query);
+        let mut content = SearchResult::new("notes.txt", "text");
+        for line in 1..=20 {
+            content.push_line(line, "dune", vec![(0, 4)], &query);
+        }
+        assert!(title.relevance() > content.relevance());
+        assert!(content.relevance() > SearchResult::new("empty.txt", "text").relevance());
+    }
+}
@@ -0,0 +1,225 @@
+use std::{sync::{atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}, Arc, Mutex}, thread, time::{Duration, Instant}};
+use crate::helpers::interrupt;
+
+/// Number of slowest files listed in the summary
+const SLOWEST_COUNT: usize = 5;
+
+/// Interval between two updates of the progress line
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Counters of a search run, updated by the search threads
+///
+/// queued: files sent to the thread pool, scanned: files read (failed or not),
+/// skipped: files excluded by the filters, failed: files whose extraction reported an error
+pub struct SearchStats {
+    start: Instant,
+    queued: AtomicUsize,
+    scanned: AtomicUsize,
+    skipped: AtomicUsize,
+    failed: AtomicUsize,
+    matched: AtomicUsize,
+    bytes: AtomicU64,
+    /// Extraction duration of each scanned file
+    durations: Mutex<Vec<(Duration, String)>>,
+    /// Files and their extraction errors
+    failures: Mutex<Vec<(String, Vec<String>)>>,
+    /// Files searched without match (-L)
+    unmatched: Mutex<Vec<String>>,
+    /// Stop the search at the first file matched (-q)
+    stop_on_match: AtomicBool,
+    /// Stop the search at the first file searched without match (-q -L)
+    stop_on_unmatched: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl SearchStats {
+    pub fn new() -> Self {
+        SearchStats {
+            start: Instant::now(),
+            queued: AtomicUsize::new(0),
+            scanned: AtomicUsize::new(0),
+            skipped: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            matched: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+            durations: Mutex::new(vec![]),
+            failures: Mutex::new(vec![]),
+            unmatched: Mutex::new(vec![]),
+            stop_on_match: AtomicBool::new(false),
+            stop_on_unmatched: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    pub fn queue(&self) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn skip(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn matched(&self) {
+        self.matched.fetch_add(1, Ordering::Relaxed);
+        if self.stop_on_match.load(Ordering::Relaxed) {
+            interrupt::stop();
+        }
+    }
+
+    pub fn matched_count(&self) -> usize {
+        self.matched.load(Ordering::Relaxed)
+    }
+
+    /// A file searched without match
+    pub fn unmatched(&self, file_path: &str) {
+        if let Ok(mut unmatched) = self.unmatched.lock() {
+            unmatched.push(file_path.to_string());
+        }
+        if self.stop_on_unmatched.load(Ordering::Relaxed) {
+            interrupt::stop();
+        }
+    }
+
+    /// Files searched without match, sorted by path
+    pub fn files_without_match(&self) -> Vec<String> {
+        let mut unmatched = self.unmatched.lock().map(|u| u.clone()).unwrap_or_default();
+        unmatched.sort();
+        unmatched
+    }
+
+    /// Stop queuing files once a file is matched
+    pub fn stop_on_match(&self) {
+        self.stop_on_match.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop queuing files once a file is searched without match
+    pub fn stop_on_unmatched(&self) {
+        self.stop_on_unmatched.store(true, Ordering::Relaxed);
+    }
+
+    /// A file read in duration, with the errors of its extractors
+    pub fn scan(&self, file_path: &str, size: u64, duration: Duration, errors: Vec<String>) {
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        if let Ok(mut durations) = self.durations.lock() {
+            durations.push((duration, file_path.to_string()));
+        }
+        if !errors.is_empty() {
+            self.fail(file_path, errors);
+        }
+    }
+
+    /// A file found without being read (catalog), it has no duration
+    pub fn found(&self, size: u64) {
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// A file that could not be read, or whose extraction failed partly
+    pub fn fail(&self, file_path: &str, errors: Vec<String>) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.push((file_path.to_string(), errors));
+        }
+    }
+
+    /// files queued, scanned, skipped, failed and throughput
+    pub fn progress_line(&self) -> String {
+        let elapsed = self.start.elapsed().as_secs_f64().max(0.001);
+        let scanned = self.scanned.load(Ordering::Relaxed);
+        format!(
+            "{} queued, {scanned} scanned, {} skipped, {} failed, {:.0} files/s, {:.1} MB/s",
+            self.queued.load(Ordering::Relaxed),
+            self.skipped.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            scanned as f64 / elapsed,
+            self.bytes.load(Ordering::Relaxed) as f64 / elapsed / 1_048_576.,
+        )
+    }
+
+    /// Print the progress line on stderr until finish is called
+    pub fn show_progress(stats: &Arc<SearchStats>) -> thread::JoinHandle<()> {
+        let stats = Arc::clone(stats);
+        thread::spawn(move || {
+            while !stats.finished.load(Ordering::Relaxed) {
+                eprint!("\r{}\x1b[K", stats.progress_line());
+                thread::sleep(PROGRESS_INTERVAL);
+            }
+            // Clear the line before the results
+            eprint!("\r\x1b[K");
+        })
+    }
+
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Totals, slowest files and failed files
+    pub fn summary(&self) -> String {
+        let elapsed = self.start.elapsed();
+        let matched = self.matched.load(Ordering::Relaxed);
+        let mut summary = format!(
+            "{matched} {} matched, {} scanned, {} skipped, {} failed in {:.1}s",
+            if matched == 1 { "file" } else { "files" },
+            self.scanned.load(Ordering::Relaxed),
+            self.skipped.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            elapsed.as_secs_f64(),
+        );
+
+        let mut durations = self.durations.lock().map(|d| d.clone()).unwrap_or_default();
+        durations.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        if durations.len() > 1 {
+            summary.push_str("\nSlowest files:");
+            for (duration, file_path) in durations.iter().take(SLOWEST_COUNT) {
+                summary.push_str(&format!("\n    {:>7.2}s  {file_path}", duration.as_secs_f64()));
+            }
+        }
+
+        let mut failures = self.failures.lock().map(|f| f.clone()).unwrap_or_default();
+        failures.sort();
+        if !failures.is_empty() {
+            summary.push_str("\nFailed files:");
+            for (file_path, errors) in failures {
+                summary.push_str(&format!("\n    {file_path}: {}", errors.join("; ")));
+            }
+        }
+        summary
+    }
+}
+
+impl Default for SearchStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats_summary() {
+        let stats = SearchStats::new();
+        stats.queue();
+        stats.queue();
+        stats.skip();
+        stats.scan("/docs/a.txt", 10, Duration::from_millis(20), vec![]);
+        stats.scan("/docs/b.pdf", 10, Duration::from_millis(1500), vec![String::from("invalid xref")]);
+        stats.matched();
+
+        assert!(stats.progress_line().starts_with("2 queued, 2 scanned, 1 skipped, 1 failed"));
+        assert_eq!("\
+1 file matched, 2 scanned, 1 skipped, 1 failed in 0.0s
+Slowest files:
+       1.50s  /docs/b.pdf
+       0.02s  /docs/a.txt
+Failed files:
+    /docs/b.pdf: invalid xref", stats.summary());
+
+        stats.unmatched("/docs/c.txt");
+        stats.unmatched("/docs/a.md");
+        assert_eq!(vec!["/docs/a.md", "/docs/c.txt"], stats.files_without_match());
+        assert_eq!(1, stats.matched_count());
+    }
+}
@@ -1,48 +1,91 @@
-use std::sync::mpsc::Sender;
-use crate::helpers::{file::{self, get_file_name}, string::text_contains};
-use super::{format_file_display, format_line_found, SearchOption};
+use std::{fs::File, io::{self, BufReader}};
+use crate::helpers::file::{encoding::{self, TextLines}, get_file_name};
+use super::{SearchOption, document::{search_lines, Document, Section}, result::SearchResult};
 
 ///
-/// 
+///
 /// cargo run -- search ./src fn
 /// cargo run -- search ./Cargo.toml opt
-/// 
+/// cargo run -- search --binary=name-only ./target oms
+///
 pub struct TextSearch<'a> {
     pub file_path: &'a String,
-    pub search_term: &'a String,
     pub search_option: &'a SearchOption,
 }
 
 impl<'a> TextSearch<'a> {
-    pub fn search(&self, tx: Sender<String>) {
-        // TODO :search in file name
-        let mut result = String::new();
-        let file_name = get_file_name(&self.file_path).to_lowercase();
+    /// File name and lines, decoded from UTF-8, UTF-16 or Windows-1252
+    ///
+    /// Binary files (--binary): skipped (None), only the file name searched, or searched as text
+    pub fn document(&self) -> Option<Document> {
+        let (mut document, lines) = self.open()?;
+        if let Some(lines) = lines {
+            match lines.map(|line| line.map(trim_line_end)).collect::<io::Result<Vec<String>>>() {
+                Ok(lines) => document.sections.push(Section::Lines { lines }),
+                Err(err) => document.push_error(&err.to_string()),
+            }
+        }
+        Some(document)
+    }
 
-        let mut line_found = false;
+    /// Same as searching the document, but the lines are searched as they are read:
+    /// only the lines found and their context are kept in memory (large logs)
+    ///
+    /// Returns the result if the file matches, and the errors of the reader (None for a skipped binary file)
+    pub fn search(&self) -> Option<(Option<SearchResult>, Vec<String>)> {
+        let (mut document, lines) = self.open()?;
+        let mut query_match = self.search_option.query.matcher();
+        let mut result = SearchResult::new(self.file_path, &document.kind);
+        document.search_sections(&mut query_match, &mut result, self.search_option);
+        if let Some(lines) = lines {
+            let mut error = None;
+            let lines = lines
+                .map_while(|line| line.map_err(|err| error = Some(err)).ok())
+                .map(trim_line_end);
+            search_lines(lines, &mut query_match, &mut result, self.search_option);
+            if let Some(err) = error {
+                document.push_error(&err.to_string());
+            }
+        }
+        Some((query_match.is_match().then_some(result), document.errors))
+    }
 
-        if text_contains(&file_name, &self.search_term) {
-            result.push_str(&format_file_display(&self.file_path));
-            line_found = true;
+    /// Document with the file name, and the lines to read (None when only the name of a binary file is searched),
+    /// None for the binary files skipped
+    fn open(&self) -> Option<(Document, Option<TextLines<BufReader<File>>>)> {
+        let mut document = Document::new(self.file_path, "text");
+        let is_binary = match encoding::is_binary_file(self.file_path) {
+            Ok(is_binary) => is_binary,
+            Err(err) => {
+                document.push_error(&err.to_string());
+                return Some((document, None));
+            },
+        };
+        if is_binary && self.search_option.binary == "skip" {
+            return None;
+        }
+        document.push_field("File", &get_file_name(self.file_path));
+        if is_binary && self.search_option.binary == "name-only" {
+            return Some((document, None));
         }
 
-        if let Some(lines) = file::read_lines(&self.file_path) {
-            let mut lines = lines.enumerate();
-            while let Some((line_number, result_line)) = lines.next() {
-                if let Ok(line_text) = result_line {
-                    if text_contains(&line_text, &self.search_term) {
-                        if line_found == false {
-                            result.push_str(&format_file_display(&self.file_path));
-                            line_found = true;
-                        }
-                        result.push_str(&format_line_found(&line_number.to_string(), &line_text, &self.search_option));
-                    }
-                }
-            }
+        match encoding::read_lines(self.file_path) {
+            Ok(lines) => Some((document, Some(lines))),
+            Err(err) => {
+                document.push_error(&err.to_string());
+                Some((document, None))
+            },
         }
+    }
+}
 
-        if !result.is_empty() {
-            tx.send(result).unwrap_or_default();
+/// Line without its line break (\n or \r\n)
+fn trim_line_end(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
         }
-    }    
+    }
+    line
 }
@@ -1,55 +1,44 @@
-use std::sync::mpsc::Sender;
-use crate::helpers::{file, string::text_contains, media::video};
-use super::{option::SearchOption, format_file_display, format_line_found};
+use crate::helpers::{file, media::video::{self, subtitle}};
+use super::{option::SearchOption, document::{Document, Section}};
 
 ///
 /// cargo run -- search --cache-path="/media/solofo/MEDIA/.oms" "/media/solofo/MEDIA/films/" fire
 /// 
 pub struct VideoSearch<'a> {
     pub file_path: &'a String,
-    pub search_term: &'a String,
     pub search_option: &'a SearchOption,   
 }
 
 impl<'a> VideoSearch<'a> {
-    pub fn search(&self, tx: Sender<String>) {
-        let mut result = String::new();
-        let file_name = file::get_file_name(&self.file_path).to_lowercase();
-
-        let mut found: Vec<(String, String)> = vec![];
-
-        if text_contains(&file_name, &self.search_term) {
-            found.push(("File".to_string(), file_name.clone()));
-        }
+    pub fn document(&self) -> Document {
+        let mut document = Document::new(self.file_path, "video");
+        document.push_field("File", &file::get_file_name(self.file_path).to_lowercase());
 
         let videos = video::result::get_video_result(
-                &self.file_path,
+                self.file_path,
                 &String::new(),
                 &self.search_option.provider
-            ).unwrap_or(vec![]);
-        if videos.len() == 0 {
-            return;
-        }
+            ).unwrap_or_else(|err| {
+                document.push_error(&err.to_string());
+                vec![]
+            });
 
         for video in &videos {
-            let search_results = video.search(&self.search_term);
-            if search_results.len() == 0 {
-                continue;
-            }
-            for (key, value) in &search_results {
-                found.push((key.to_string(), value.to_string()));
+            for (key, value) in video.fields() {
+                document.push_field(key, &value);
             }
         }
 
-        if found.len() > 0 {
-            result.push_str(&format_file_display(&self.file_path));
-            found.iter().for_each(|(item, text)| {
-                result.push_str(&format_line_found(&item.to_string(), &text, &self.search_option));
-            });
-        }
-
-        if !result.is_empty() {
-            tx.send(result).unwrap_or_default();
+        // Dialogues of the sidecar and embedded subtitles
+        for subtitle in subtitle::get_subtitles(self.file_path) {
+            for cue in subtitle.cues {
+                document.sections.push(Section::Cue {
+                    source: subtitle.source.clone(),
+                    timestamp: subtitle::format_timestamp(cue.start),
+                    text: cue.text,
+                });
+            }
         }
+        document
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
 mod option;
 
 use std::{collections::HashMap, fs, io, path::Path, sync::{Arc, Mutex}, thread};
-use crate::helpers::{file, media::video, threadpool::ThreadPool};
+use crate::helpers::{file::{self, pattern::{glob_files, is_pattern}, walk::walk}, media::video, threadpool::ThreadPool};
 use colored::Colorize;
 use regex::Regex;
 use self::option::TranscodeOption;
@@ -58,6 +58,16 @@ impl Runnable for Transcode {
                 "c" | "check" => transcode_option.set_check(),
                 "f" | "force" => transcode_option.set_force(),
                 "t" | "thread" => transcode_option.set_thread(value)?,
+                "max-depth" => transcode_option.walk.set_max_depth(value)?,
+                "hidden" => transcode_option.walk.set_hidden(),
+                "follow-symlinks" => transcode_option.walk.set_follow_symlinks(),
+                "min-size" => transcode_option.filter.set_min_size(value)?,
+                "max-size" => transcode_option.filter.set_max_size(value)?,
+                "newer-than" => transcode_option.filter.set_newer_than(value)?,
+                "older-than" => transcode_option.filter.set_older_than(value)?,
+                "min-duration" => transcode_option.filter.set_min_duration(value)?,
+                "codec" => transcode_option.filter.set_codec(value)?,
+                "resolution" => transcode_option.filter.set_resolution(value)?,
                 "e" | "extensions" => transcode_option.extensions_from(value)?,
                 "o" | "output" => transcode_option.set_output(value)?,
                 "s" | "split" => transcode_option.set_split(value)?,
@@ -78,6 +88,7 @@ impl Runnable for Transcode {
             println!("{}", "keep-smallest ignored, -d not set".yellow());
         }
 
+        transcode_option.base_path = self.file_path.clone();
         let thread_pool = ThreadPool::new(transcode_option.thread);
 
         // if files are provided in option as list
@@ -130,13 +141,21 @@ fn file_info_from_list(transcode_option: &TranscodeOption, thread_pool: &ThreadP
             Ok(md) if md.is_file() => {
                 transcode_file(&file_path, transcode_option, thread_pool);
             },
+            // Glob or regex of the files: /media/films/**/*.avi
+            Err(_) if is_pattern(file_path) => match glob_files(file_path, &transcode_option.walk) {
+                Ok(files) => files.iter().for_each(|file_path| transcode_file(file_path, transcode_option, thread_pool)),
+                Err(err) => eprintln!("{}", format!("Warning: {err}").yellow()),
+            },
             _ => (),
         };
     }
 }
 
 fn transcode_file(file_path: &String, transcode_option: &TranscodeOption, thread_pool: &ThreadPool) {
-    if transcode_option.skip_list.contains(&file::get_file_name(file_path)) {
+    if transcode_option.is_skipped(file_path) {
+        return;
+    }
+    if !transcode_option.filter.is_match(file_path) {
         return;
     }
     // Only check
@@ -373,21 +392,9 @@ fn transcode_file_split(file_path: &String, transcode_option: &TranscodeOption)
 }
 
 fn transcode_dir(dir_path: &String, transcode_option: &TranscodeOption, thread_pool: &ThreadPool) {
-    let read_dir =  fs::read_dir(Path::new(&dir_path));
-    if read_dir.is_err() {
-        return;
-    }
-    for entry in read_dir.unwrap() {
-        if entry.is_err() {
-            continue;
-        }
-        let path = entry.unwrap().path();
-        if path.is_file() {
-            transcode_file(&path.to_str().unwrap().to_string(), transcode_option, thread_pool)
-        } else if path.is_dir() {
-            transcode_dir(&path.to_str().unwrap().to_string(), transcode_option, thread_pool)
-        }
-    }
+    walk(dir_path, &transcode_option.walk, |file_path| {
+        transcode_file(&file_path, transcode_option, thread_pool)
+    });
 }
 
 pub fn check_invalid(file_path: &String) {
@@ -410,9 +417,18 @@ transcode [options] <file_path|directory_path>
     -d  Delete original file after transcoding
     --keep-smallest Keep the smallest (size) file between input and output
     -f --force  Force transcode even if the file is already streamable
-    --list=<sting>  Path of a file containing the list of files to transcore
+    --list=<sting>  Path of a file containing the list of files to transcore, one path or glob (/media/films/**/*.avi, re:...) by line
+    --skip-list=<string>    Path of a file containing the files to skip, one file name, glob (*.sample.mkv, **/extras/**) or regex (re:...) by line
     -e <string> --extensions=<string>   Only transcode files with these extensions, separated by '{OPTION_SEPARATOR}'
     -t <int> --thread=<int> Number of threads used
+    --max-depth=<int>   Max depth of sub directories, 1 for the files of the given directory only
+    --hidden    Include hidden files and directories
+    --follow-symlinks   Follow symbolic links to directories
+    --min-size=<size> --max-size=<size>   Only files of this size, in bytes or with a unit: 500K, 4G
+    --newer-than=<date> --older-than=<date>  Only files modified after/before a date (2024-01-31) or a duration ago: 12h, 7d, 2w, 1m, 1y
+    --min-duration=<duration>   Only media lasting at least: 90 (seconds), 45min, 1h30min (ffprobe)
+    --codec=<string>    Only media with a stream using one of these codecs, separated by ',': h264,hevc,aac (ffprobe)
+    --resolution=<string>   Only videos at least this size: 720p, 1920x1080 (ffprobe)
     -o <string> --output=<string>   Output extension, default mp4, (Output can be something like flv>webm,avi>mp4,mp4)
     -s <int> --split=<int>  Number in second. Split the source file into x seconds, then transcode these parts. 
                             Use this if you want to resume transcoding. Available only for transcoding single file
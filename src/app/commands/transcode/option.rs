@@ -1,18 +1,23 @@
-use std::{io::{Error, ErrorKind}, cmp::max, collections::HashMap};
-use crate::{app::commands::OPTION_SEPARATOR, helpers::file};
+use std::{io::{Error, ErrorKind}, cmp::max, collections::HashMap, path::Path};
+use crate::{app::commands::OPTION_SEPARATOR, helpers::file::{self, filter::FilterOption, pattern::{is_pattern, FilePattern}, walk::WalkOption}};
 
 type Result<T> = std::result::Result<T, std::io::Error>;
 
 pub struct TranscodeOption {
     pub extensions: Vec<String>,
     pub thread: usize,
+    pub walk: WalkOption,
+    pub filter: FilterOption,
     pub delete: bool,
     pub keep_smallest: bool,
     pub split: usize,
     pub force: bool,
     pub check: bool,
     pub list: Vec<String>,
-    pub skip_list: Vec<String>,
+    /// File names, globs or regex (re:) of the files to skip
+    pub skip_list: Vec<FilePattern>,
+    /// Transcoded directory, skip_list patterns match the path relative to it
+    pub base_path: String,
     output_formats: HashMap<String, String>,
 }
 
@@ -21,6 +26,8 @@ impl TranscodeOption {
         TranscodeOption {
             extensions: vec![],
             thread: max(1, num_cpus::get() - 1),
+            walk: WalkOption::new(),
+            filter: FilterOption::new(),
             delete: false,
             keep_smallest: false,
             split: 0,
@@ -29,6 +36,7 @@ impl TranscodeOption {
             output_formats: HashMap::new(),
             list: vec![],
             skip_list: vec![],
+            base_path: String::new(),
         }
     }
 
@@ -134,8 +142,15 @@ impl TranscodeOption {
         if let Some(lines) = file::read_lines(value) {
             for (_, line) in lines.enumerate() {
                 if let Ok(l) = line {
-                    if !l.is_empty() {
-                        self.skip_list.push(file::get_file_name(&l.trim().to_string()));
+                    let l = l.trim();
+                    if l.is_empty() {
+                        continue;
+                    }
+                    // A listed file is skipped by name, wherever it is
+                    if is_pattern(l) && !Path::new(l).exists() {
+                        self.skip_list.push(FilePattern::new(l)?);
+                    } else {
+                        self.skip_list.push(FilePattern::from_name(&file::get_file_name(&l.to_string()))?);
                     }
                 }
             }
@@ -147,6 +162,10 @@ impl TranscodeOption {
         ));
     }
 
+    pub fn is_skipped(&self, file_path: &str) -> bool {
+        self.skip_list.iter().any(|pattern| pattern.is_match(file_path, &self.base_path))
+    }
+
     pub fn get_output(&self, extension: &String) -> String {
         if let Some(output) = self.output_formats.get(&extension.to_lowercase()) {
             return output.to_string();
@@ -179,10 +198,13 @@ impl Clone for TranscodeOption {
             output_formats: self.output_formats.clone(),
             split: self.split,
             thread: self.thread,
+            walk: self.walk.clone(),
+            filter: self.filter.clone(),
             force: self.force,
             check: self.check,
             list: self.list.clone(),
-            skip_list: self.list.clone(),
+            skip_list: self.skip_list.clone(),
+            base_path: self.base_path.clone(),
         }
     }
 }
@@ -8,6 +8,8 @@ pub mod cache;
 pub mod db;
 pub mod threadpool;
 pub mod command;
+pub mod query;
+pub mod interrupt;
 
 use std::{thread, time::Duration};
 
@@ -192,6 +192,12 @@ pub fn set_base_path(path: &str) {
     }
 }
 
+pub fn get_base_path() -> String {
+    unsafe {
+        CACHE.base_path.clone()
+    }
+}
+
 pub fn get_cache(key: &String, subdir: &str) -> Option<(String, String)> {
     unsafe {
         return CACHE.get_cache(key, subdir);
@@ -1,5 +1,6 @@
 use std::io;
 use serde::Serialize;
+use serde_json::Value;
 use url::Url;
 use crate::helpers::{http, rtrim_char};
 
@@ -48,6 +49,32 @@ impl Elastic {
         }
     }
 
+    /// Sources of the documents found by the query (body of the _search api),
+    /// and the number of documents matched, more than the sources beyond the size of the request
+    pub fn search<T: Serialize>(&self, body: &T) -> Result<(Vec<Value>, u64), io::Error> {
+        let response = http::post_body(
+            &format!("{}/_search", self.url),
+            "POST",
+            &vec![],
+            body
+        )?;
+        let json: Value = serde_json::from_str(&response).map_err(|err| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid elastic response: {err}")
+        ))?;
+        if let Some(error) = json.get("error") {
+            let reason = error.get("reason").and_then(Value::as_str).map_or(error.to_string(), String::from);
+            return Err(io::Error::other(format!("Elastic search error: {reason}")));
+        }
+        let sources: Vec<Value> = json["hits"]["hits"]
+            .as_array()
+            .map(|hits| hits.iter().map(|hit| hit["_source"].clone()).collect())
+            .unwrap_or_default();
+        // {"value": 42} since Elasticsearch 7, a number before
+        let total = json["hits"]["total"]["value"].as_u64().or(json["hits"]["total"].as_u64()).unwrap_or(sources.len() as u64);
+        Ok((sources, total))
+    }
+
     pub fn drop_index(&self) {
         let _ = http::post_body(
             &format!("{}", self.url), 
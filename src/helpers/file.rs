@@ -10,14 +10,24 @@ use image::EncodableLayout;
 use ring::digest::{Context, SHA256};
 use data_encoding::HEXUPPER;
 
+pub mod archive;
+pub mod encoding;
+pub mod magic;
+pub mod filter;
+pub mod pattern;
+pub mod walk;
+
 
 type Result<T> = std::result::Result<T, std::io::Error>;
 
 pub static VIDEO_EXTENSIONS: [&str; 30] = ["mpe", "mpv", "m2v", "m4v", "3gp", "3g2", "mp4", "mkv", "avi", "flv", "f4v", "f4p", "f4a", "f4b", "mpg", "mpeg", "mp2", "divx", "wmv", "dat", "webm", "vob", "ogv", "m4p", "ts", "webm", "mov", "ogm", "av1", "vp9"];
 pub static VIDEO_EXTENSIONS_IGNORED: [&str; 9] = ["db", "srt", "nfo", "idx", "sub", "bup", "ifo", "vob", "sfv"];
 pub static PDF_EXTENSIONS: [&str; 1] = ["pdf"];
-pub static MS_EXTENSIONS: [&str; 6] = ["doc", "docx", "odp", "odt", "pptx", "xlsx"];
+pub static EPUB_EXTENSIONS: [&str; 1] = ["epub"];
+pub static MS_EXTENSIONS: [&str; 9] = ["doc", "docx", "odp", "ods", "odt", "ppt", "pptx", "xls", "xlsx"];
 pub static IMAGE_EXTENSIONS: [&str; 11] = ["avif", "apng", "gif", "jpg", "jpeg", "jfif", "pjpeg", "pjp", "png", "webp", "heic"];
+pub static MAIL_EXTENSIONS: [&str; 2] = ["eml", "mbox"];
+pub static MARKUP_EXTENSIONS: [&str; 5] = ["htm", "html", "md", "rst", "xml"];
 pub static AUDIO_EXTENSIONS: [&str; 20] = ["wav", "wave", "aiff", "aif", "aifc", "pcm", "aiff", "au", "wav", "l16", "flac", "m4a", "caf", "wma", "mp3", "ogg", "oga", "mogg", "aac", "m4r"];
 
 /// Check if the given file exists
@@ -95,8 +105,10 @@ pub fn file_size(file_path: &str) -> Result<u64> {
   }
 }
 
+/// The type of the file is detected from its content (see magic::media_extension),
+/// from its extension if the content is unknown
 pub fn is_video_file(file_path: &String) -> bool {
-   let extension = get_extension(&file_path).to_lowercase();
+   let extension = magic::media_extension(file_path);
    return VIDEO_EXTENSIONS.contains(&extension.as_str());
 }
 
@@ -106,22 +118,22 @@ pub fn is_video_ignored_file(file_path: &String) -> bool {
 }
 
 pub fn is_image_file(file_path: &String) -> bool {
-   let extension = get_extension(&file_path).to_lowercase();
+   let extension = magic::media_extension(file_path);
    return IMAGE_EXTENSIONS.contains(&extension.as_str());
 }
 
 pub fn is_audio_file(file_path: &String) -> bool {
-   let extension = get_extension(&file_path).to_lowercase();
+   let extension = magic::media_extension(file_path);
    return AUDIO_EXTENSIONS.contains(&extension.as_str());
 }
 
 pub fn is_pdf_file(file_path: &String) -> bool {
-   let extension = get_extension(&file_path).to_lowercase();
+   let extension = magic::media_extension(file_path);
    return PDF_EXTENSIONS.contains(&extension.as_str());
 }
 
 pub fn is_ms_file(file_path: &String) -> bool {
-   let extension = get_extension(&file_path).to_lowercase();
+   let extension = magic::media_extension(file_path);
    return MS_EXTENSIONS.contains(&extension.as_str());
 }
 
@@ -0,0 +1,140 @@
+use std::{fs::{self, File}, io::{self, BufReader, Read}, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}};
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Separator between the archive path and the path of a file inside: archive.zip!/dir/file.txt
+pub const ARCHIVE_PATH_SEPARATOR: &str = "!/";
+
+pub static ARCHIVE_EXTENSIONS: [&str; 4] = ["zip", "tar", "tar.gz", "tgz"];
+
+/// Counter of the temporary directories created by this process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Format of an archive: zip, tar.gz or tar, None for the other files,
+/// documents using zip (docx, epub...) are not archives
+///
+/// # Arguments
+///
+/// * `file_path` - path of the file, tar.gz is told from gz by the name
+/// * `extension` - type of the file detected by magic::media_extension
+pub fn archive_format(file_path: &str, extension: &str) -> Option<&'static str> {
+    match extension {
+        "zip" => Some("zip"),
+        "tgz" => Some("tar.gz"),
+        "gz" if file_path.to_lowercase().ends_with(".tar.gz") => Some("tar.gz"),
+        "tar" => Some("tar"),
+        _ => None,
+    }
+}
+
+/// Call callback with the path and the content reader of each file of the archive
+///
+/// # Arguments
+///
+/// * `file_path` - path of the archive
+/// * `format` - zip, tar.gz or tar, given by archive_format
+/// * `callback` - called with the path of the file inside the archive, and its content
+pub fn for_each_entry<F>(file_path: &str, format: &str, mut callback: F) -> Result<()>
+where
+    F: FnMut(&str, &mut dyn Read),
+{
+    let file = BufReader::new(File::open(file_path)?);
+    match format {
+        "zip" => {
+            let mut archive = ZipArchive::new(file)?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index)?;
+                if entry.is_file() {
+                    let name = entry.name().to_string();
+                    callback(&name, &mut entry);
+                }
+            }
+            Ok(())
+        },
+        "tar.gz" => for_each_tar_entry(tar::Archive::new(GzDecoder::new(file)), callback),
+        "tar" => for_each_tar_entry(tar::Archive::new(file), callback),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{file_path} is not an archive")
+        )),
+    }
+}
+
+fn for_each_tar_entry<R, F>(mut archive: tar::Archive<R>, mut callback: F) -> Result<()>
+where
+    R: Read,
+    F: FnMut(&str, &mut dyn Read),
+{
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let name = entry.path()?.display().to_string();
+            callback(&name, &mut entry);
+        }
+    }
+    Ok(())
+}
+
+/// Copy the content of an archive entry in a new temporary directory, keeping its file name
+///
+/// The caller removes the directory (parent of the returned path) when done
+pub fn extract_to_temp(entry_path: &str, content: &mut dyn Read) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!(
+        "oms_archive_{}_{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+    let file_name = Path::new(entry_path)
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or("entry".into());
+    let temp_path = dir.join(file_name);
+    let mut temp_file = File::create(&temp_path)?;
+    if let Err(err) = io::copy(content, &mut temp_file) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(err);
+    }
+    Ok(temp_path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use super::super::magic;
+
+    #[test]
+    fn archive_zip_entries() {
+        // Detected from the content, whatever the name
+        let zip_path = std::env::temp_dir().join(format!("oms_archive_test_{}.dat", std::process::id()));
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        writer.add_directory("dir/", Default::default()).unwrap();
+        writer.start_file("dir/a.txt", Default::default()).unwrap();
+        writer.write_all(b"hello").unwrap();
+        writer.finish().unwrap();
+
+        let zip_path = zip_path.display().to_string();
+        let format = archive_format(&zip_path, &magic::media_extension(&zip_path));
+        assert_eq!(Some("zip"), format);
+
+        let mut entries = vec![];
+        for_each_entry(&zip_path, format.unwrap(), |name, content| {
+            let mut text = String::new();
+            content.read_to_string(&mut text).unwrap();
+            entries.push((name.to_string(), text));
+        }).unwrap();
+        assert_eq!(vec![("dir/a.txt".to_string(), "hello".to_string())], entries);
+        let _ = fs::remove_file(&zip_path);
+    }
+
+    #[test]
+    fn archive_extensions() {
+        assert_eq!(Some("tar.gz"), archive_format("/tmp/delivery.TAR.GZ", "gz"));
+        assert_eq!(Some("tar.gz"), archive_format("delivery.tgz", "tgz"));
+        assert_eq!(None, archive_format("book.epub", "epub"));
+        assert_eq!(None, archive_format("file.gz", "gz"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::{fs::File, io::{self, BufRead, BufReader, Read}};
+use encoding_rs::{Decoder, UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Number of bytes read to check if a file is binary
+const SNIFF_LENGTH: usize = 8000;
+
+/// Check if the beginning of the file contains a NUL byte (executables, databases, images...)
+pub fn is_binary_file(file_path: &str) -> Result<bool> {
+    let mut buffer = Vec::with_capacity(SNIFF_LENGTH);
+    File::open(file_path)?.take(SNIFF_LENGTH as u64).read_to_end(&mut buffer)?;
+    Ok(is_binary(&buffer))
+}
+
+/// NUL bytes are not found in text, except in UTF-16 (detected with its BOM)
+pub fn is_binary(bytes: &[u8]) -> bool {
+    let bytes = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+    utf16_bom(bytes).is_none() && bytes.contains(&0)
+}
+
+/// Read a text file as UTF-8, UTF-16 (with BOM) or Windows-1252 (Latin-1)
+pub fn read_text(file_path: &str) -> Result<String> {
+    read_lines(file_path)?.collect()
+}
+
+/// Lines of a text file, decoded as they are read (see TextLines)
+pub fn read_lines(file_path: &str) -> Result<TextLines<BufReader<File>>> {
+    TextLines::new(BufReader::new(File::open(file_path)?))
+}
+
+/// Decode text bytes:
+/// UTF-16 if it starts with a BOM, UTF-8 if valid,
+/// otherwise each line that is not valid UTF-8 is decoded as Windows-1252
+pub fn decode(bytes: &[u8]) -> Result<String> {
+    TextLines::new(bytes)?.collect()
+}
+
+/// Lines of a text, with their line break, decoded one at a time:
+/// UTF-16 if it starts with a BOM, otherwise UTF-8, or Windows-1252 for the lines that are not valid UTF-8
+pub struct TextLines<R: BufRead> {
+    reader: R,
+    /// UTF-16 decoder, None once the end is decoded
+    utf16: Option<Decoder>,
+    is_utf16: bool,
+    /// UTF-16 text decoded and not returned yet
+    decoded: String,
+    first_line: bool,
+}
+
+impl<R: BufRead> TextLines<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let utf16 = utf16_bom(reader.fill_buf()?).map(|little_endian| {
+            if little_endian { UTF_16LE.new_decoder_with_bom_removal() } else { UTF_16BE.new_decoder_with_bom_removal() }
+        });
+        Ok(TextLines {
+            reader,
+            is_utf16: utf16.is_some(),
+            utf16,
+            decoded: String::new(),
+            first_line: true,
+        })
+    }
+
+    fn next_utf16_line(&mut self) -> Option<Result<String>> {
+        loop {
+            if let Some(end) = self.decoded.find('\n') {
+                return Some(Ok(self.decoded.drain(..=end).collect()));
+            }
+            let Some(decoder) = &mut self.utf16 else {
+                return (!self.decoded.is_empty()).then(|| Ok(std::mem::take(&mut self.decoded)));
+            };
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) => return Some(Err(err)),
+            };
+            let last = chunk.is_empty();
+            self.decoded.reserve(decoder.max_utf8_buffer_length(chunk.len()).unwrap_or_default());
+            let (_, read, _) = decoder.decode_to_string(chunk, &mut self.decoded, last);
+            self.reader.consume(read);
+            if last {
+                self.utf16 = None;
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TextLines<R> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_utf16 {
+            return self.next_utf16_line();
+        }
+        let mut line = vec![];
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                let bytes = match std::mem::take(&mut self.first_line) {
+                    true => line.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&line),
+                    false => &line,
+                };
+                Some(Ok(match std::str::from_utf8(bytes) {
+                    Ok(text) => text.to_string(),
+                    Err(_) => decode_windows_1252(bytes),
+                }))
+            },
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Encode text as the original bytes were: UTF-16 with the same BOM, or UTF-8 (with its BOM if any)
+///
+/// Other encodings are not written back, as lines may have been decoded differently
+pub fn encode_as(text: &str, original: &[u8]) -> Result<Vec<u8>> {
+    if let Some(little_endian) = utf16_bom(original) {
+        let mut bytes = if little_endian { vec![0xff, 0xfe] } else { vec![0xfe, 0xff] };
+        for unit in text.encode_utf16() {
+            bytes.extend(if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+        return Ok(bytes);
+    }
+    let bom = original.starts_with(b"\xef\xbb\xbf");
+    if std::str::from_utf8(if bom { &original[3..] } else { original }).is_err() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not UTF-8 or UTF-16 text"));
+    }
+    let mut bytes = if bom { b"\xef\xbb\xbf".to_vec() } else { vec![] };
+    bytes.extend(text.as_bytes());
+    Ok(bytes)
+}
+
+/// Windows-1252 text (Latin-1 with the chars of the bytes 0x80 to 0x9f)
+pub fn decode_windows_1252(bytes: &[u8]) -> String {
+    WINDOWS_1252.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// Some(true) for UTF-16 little endian, Some(false) for big endian
+fn utf16_bom(bytes: &[u8]) -> Option<bool> {
+    match bytes.get(..2) {
+        Some([0xff, 0xfe]) => Some(true),
+        Some([0xfe, 0xff]) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encoding_decode() {
+        assert_eq!("été", decode("été".as_bytes()).unwrap());
+        assert_eq!("Noël", decode(b"\xef\xbb\xbfNo\xc3\xabl").unwrap());
+        // Latin-1 line next to a UTF-8 line
+        assert_eq!("à l'été\n“déjà”", decode(b"\xe0 l'\xe9t\xe9\n\xe2\x80\x9cd\xc3\xa9j\xc3\xa0\xe2\x80\x9d").unwrap());
+        assert_eq!("œuvre…", decode(b"\x9cuvre\x85").unwrap());
+        assert_eq!("Hé", decode(b"\xff\xfeH\x00\xe9\x00").unwrap());
+        assert_eq!("Hé", decode(b"\xfe\xff\x00H\x00\xe9").unwrap());
+    }
+
+    #[test]
+    fn encoding_text_lines() {
+        let lines: Vec<String> = TextLines::new(&b"\xef\xbb\xbfone\r\n\xe9t\xe9\nlast"[..]).unwrap().map(Result::unwrap).collect();
+        assert_eq!(vec!["one\r\n", "été\n", "last"], lines);
+        // UTF-16 read in small chunks
+        let bytes: Vec<u8> = [0xff, 0xfe].into_iter().chain("ab\nçé\n".encode_utf16().flat_map(|unit| unit.to_le_bytes())).collect();
+        let lines: Vec<String> = TextLines::new(BufReader::with_capacity(3, &bytes[..])).unwrap().map(Result::unwrap).collect();
+        assert_eq!(vec!["ab\n", "çé\n"], lines);
+    }
+
+    #[test]
+    fn encoding_encode_as() {
+        assert_eq!(b"\xff\xfeN\x00o\x00".to_vec(), encode_as("No", b"\xff\xfeH\x00").unwrap());
+        assert_eq!(b"\xef\xbb\xbf\xc3\xa9".to_vec(), encode_as("é", b"\xef\xbb\xbfe").unwrap());
+        assert!(encode_as("é", b"\xe9t\xe9").is_err());
+    }
+
+    #[test]
+    fn encoding_binary() {
+        assert!(is_binary(b"\x7fELF\x02\x01\x01\x00\x00"));
+        assert!(!is_binary(b"\xff\xfeH\x00i\x00"));
+        assert!(!is_binary("Un été".as_bytes()));
+    }
+}
@@ -0,0 +1,297 @@
+use std::{fs, io::{Error, ErrorKind}, time::{SystemTime, UNIX_EPOCH}};
+use chrono::{Local, NaiveDate, TimeZone};
+use serde_json::Value;
+use crate::helpers::command;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Filters on file properties,
+/// shared by the commands processing files (search, info, transcode)
+///
+/// Media filters (duration, codec, resolution) are evaluated with ffprobe,
+/// files without media stream don't match them
+#[derive(Clone, Default)]
+pub struct FilterOption {
+    /// Size in bytes
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Modification time in seconds since epoch
+    pub newer_than: Option<u64>,
+    pub older_than: Option<u64>,
+    /// Duration in seconds
+    pub min_duration: Option<f64>,
+    /// Codec names (h264, hevc, aac...), one of the streams must use one of them
+    pub codecs: Vec<String>,
+    /// Min width and height of the video stream
+    pub resolution: Option<(u64, u64)>,
+}
+
+/// Streams properties read by ffprobe
+#[derive(Debug, Default, PartialEq)]
+struct MediaProbe {
+    duration: f64,
+    codecs: Vec<String>,
+    /// Largest video stream
+    width: u64,
+    height: u64,
+}
+
+impl FilterOption {
+    pub fn new() -> Self {
+        FilterOption::default()
+    }
+
+    /// --min-size=4G: bytes, or with a unit K, M, G, T (1024 based)
+    pub fn set_min_size(&mut self, value: &str) -> Result<()> {
+        self.min_size = Some(parse_size(value)?);
+        Ok(())
+    }
+
+    pub fn set_max_size(&mut self, value: &str) -> Result<()> {
+        self.max_size = Some(parse_size(value)?);
+        Ok(())
+    }
+
+    /// --newer-than=2024-01-31 or a duration before now: 12h, 7d, 2w, 1m (30 days), 1y
+    pub fn set_newer_than(&mut self, value: &str) -> Result<()> {
+        self.newer_than = Some(parse_date(value)?);
+        Ok(())
+    }
+
+    pub fn set_older_than(&mut self, value: &str) -> Result<()> {
+        self.older_than = Some(parse_date(value)?);
+        Ok(())
+    }
+
+    /// --min-duration=90 (seconds), 45min, 1h30min
+    pub fn set_min_duration(&mut self, value: &str) -> Result<()> {
+        self.min_duration = Some(parse_duration(value)? as f64);
+        Ok(())
+    }
+
+    /// --codec=h264,hevc
+    pub fn set_codec(&mut self, value: &str) -> Result<()> {
+        self.codecs = value
+            .split(',')
+            .map(|codec| codec.trim().to_lowercase())
+            .filter(|codec| !codec.is_empty())
+            .collect();
+        if self.codecs.is_empty() {
+            return Err(invalid_value("codec"));
+        }
+        Ok(())
+    }
+
+    /// --resolution=1920x1080 or 720p: min size of the video
+    pub fn set_resolution(&mut self, value: &str) -> Result<()> {
+        let value = value.to_lowercase();
+        let resolution = if let Some(height) = value.strip_suffix('p') {
+            height.parse::<u64>().ok().map(|height| (0, height))
+        } else if let Some((width, height)) = value.split_once('x') {
+            width.parse::<u64>().ok().zip(height.parse::<u64>().ok())
+        } else {
+            None
+        };
+        match resolution {
+            Some(resolution) => {
+                self.resolution = Some(resolution);
+                Ok(())
+            },
+            None => Err(invalid_value("resolution")),
+        }
+    }
+
+    pub fn has_media_filter(&self) -> bool {
+        self.min_duration.is_some() || !self.codecs.is_empty() || self.resolution.is_some()
+    }
+
+    /// Check the file against all the filters set
+    pub fn is_match(&self, file_path: &str) -> bool {
+        if self.min_size.is_some() || self.max_size.is_some() || self.newer_than.is_some() || self.older_than.is_some() {
+            let Ok(metadata) = fs::metadata(file_path) else {
+                return false;
+            };
+            let size = metadata.len();
+            if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+                return false;
+            }
+            let modification_time = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_secs());
+            if self.newer_than.is_some_and(|time| modification_time < time)
+                || self.older_than.is_some_and(|time| modification_time >= time) {
+                return false;
+            }
+        }
+        if self.has_media_filter() {
+            return self.is_media_match(&probe(file_path));
+        }
+        true
+    }
+
+    fn is_media_match(&self, media: &MediaProbe) -> bool {
+        if self.min_duration.is_some_and(|min| media.duration < min) {
+            return false;
+        }
+        if !self.codecs.is_empty() && !media.codecs.iter().any(|codec| self.codecs.contains(codec)) {
+            return false;
+        }
+        if let Some((width, height)) = self.resolution {
+            if media.width < width || media.height < height {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Duration, codecs and video size of a media file
+fn probe(file_path: &str) -> MediaProbe {
+    let output = command::exec(
+        "ffprobe",
+        ["-v", "error", "-show_entries", "format=duration:stream=codec_name,codec_type,width,height", "-of", "json", file_path]
+    );
+    parse_probe(&output)
+}
+
+fn parse_probe(output: &str) -> MediaProbe {
+    let mut media = MediaProbe::default();
+    let Ok(json) = serde_json::from_str::<Value>(output) else {
+        return media;
+    };
+    media.duration = json["format"]["duration"]
+        .as_str()
+        .and_then(|duration| duration.parse::<f64>().ok())
+        .unwrap_or_default();
+    for stream in json["streams"].as_array().into_iter().flatten() {
+        if let Some(codec) = stream["codec_name"].as_str() {
+            media.codecs.push(codec.to_lowercase());
+        }
+        if stream["codec_type"] == "video" {
+            let width = stream["width"].as_u64().unwrap_or_default();
+            let height = stream["height"].as_u64().unwrap_or_default();
+            if width * height > media.width * media.height {
+                media.width = width;
+                media.height = height;
+            }
+        }
+    }
+    media
+}
+
+fn parse_size(value: &str) -> Result<u64> {
+    let value = value.trim().to_uppercase();
+    let value = value.trim_end_matches('B').trim_end_matches('I');
+    let (number, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit() && *c != '.') {
+        Some((index, _)) => value.split_at(index),
+        None => (value, ""),
+    };
+    let multiplier: u64 = match unit.trim() {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(invalid_value("size")),
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number >= 0. => Ok((number * multiplier as f64) as u64),
+        _ => Err(invalid_value("size")),
+    }
+}
+
+/// Date (YYYY-MM-DD, local time) or duration before now, in seconds since epoch
+fn parse_date(value: &str) -> Result<u64> {
+    if let Ok(date) = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|datetime| Local.from_local_datetime(&datetime).earliest())
+            .map(|datetime| datetime.timestamp().max(0) as u64)
+            .ok_or(invalid_value("date"));
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    Ok(now.saturating_sub(parse_duration(value)?))
+}
+
+/// 90 (seconds), 30s, 45min, 12h, 7d, 2w, 1m (30 days), 1y (365 days), or combined: 1h30min
+fn parse_duration(value: &str) -> Result<u64> {
+    let value = value.trim().to_lowercase();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Ok(seconds);
+    }
+    let mut seconds = 0;
+    let mut number = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let mut unit = c.to_string();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+            unit.push(c);
+        }
+        let multiplier = match unit.as_str() {
+            "s" => 1,
+            "min" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            "w" => 7 * 86400,
+            "m" => 30 * 86400,
+            "y" => 365 * 86400,
+            _ => return Err(invalid_value("duration")),
+        };
+        seconds += number.parse::<u64>().map_err(|_| invalid_value("duration"))? * multiplier;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid_value("duration"));
+    }
+    Ok(seconds)
+}
+
+fn invalid_value(name: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("Invalid value for {name}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn filter_parse_values() {
+        assert_eq!(4 << 30, parse_size("4G").unwrap());
+        assert_eq!(1536, parse_size("1.5kb").unwrap());
+        assert_eq!(500, parse_size("500").unwrap());
+        assert!(parse_size("4X").is_err());
+        assert_eq!(5400, parse_duration("1h30min").unwrap());
+        assert_eq!(7 * 86400, parse_duration("7d").unwrap());
+        assert!(parse_duration("7 days").is_err());
+
+        let mut filter = FilterOption::new();
+        filter.set_resolution("720p").unwrap();
+        assert_eq!(Some((0, 720)), filter.resolution);
+        filter.set_resolution("1920x1080").unwrap();
+        assert_eq!(Some((1920, 1080)), filter.resolution);
+    }
+
+    #[test]
+    fn filter_media() {
+        let media = parse_probe(r#"{"streams": [
+            {"codec_name": "h264", "codec_type": "video", "width": 1920, "height": 1080},
+            {"codec_name": "aac", "codec_type": "audio"}
+        ], "format": {"duration": "5400.120000"}}"#);
+        let mut filter = FilterOption::new();
+        filter.set_codec("hevc,AAC").unwrap();
+        filter.set_min_duration("90min").unwrap();
+        filter.set_resolution("1080p").unwrap();
+        assert!(filter.is_media_match(&media));
+        filter.set_resolution("2160p").unwrap();
+        assert!(!filter.is_media_match(&media));
+        assert!(!filter.is_media_match(&MediaProbe::default()));
+    }
+}
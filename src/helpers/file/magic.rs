@@ -0,0 +1,222 @@
+use std::{fs::File, io::Read};
+use zip::ZipArchive;
+use crate::helpers::media::office::cfb::{stream_names, CFB_SIGNATURE};
+use super::get_extension;
+
+/// Number of bytes read to detect the type of a file
+const SNIFF_LENGTH: u64 = 4096;
+
+/// Size of a MPEG transport stream packet, each one starts with 0x47
+const TS_PACKET_SIZE: usize = 188;
+
+/// Mimetype entry of the OpenDocument and EPUB files, first entry of the zip stored as is
+const ZIP_MIMETYPES: [(&str, &str); 4] = [
+    ("application/epub+zip", "epub"),
+    ("application/vnd.oasis.opendocument.text", "odt"),
+    ("application/vnd.oasis.opendocument.spreadsheet", "ods"),
+    ("application/vnd.oasis.opendocument.presentation", "odp"),
+];
+
+/// Main part of the Office Open XML files
+const OOXML_PARTS: [(&str, &str); 3] = [
+    ("word/document.xml", "docx"),
+    ("xl/workbook.xml", "xlsx"),
+    ("ppt/presentation.xml", "pptx"),
+];
+
+/// Extension of the file type, from its content, or from its name if the content is unknown
+/// (text, missing file, formats without signature)
+///
+/// # Examples
+///
+/// ```
+/// use oms::helpers::file::magic;
+///
+/// assert_eq!("txt", magic::media_extension("/tmp/missing-file.TXT"));
+/// ```
+pub fn media_extension(file_path: &str) -> String {
+    match detect_extension(file_path) {
+        Some(extension) => extension.to_string(),
+        None => get_extension(file_path).to_lowercase(),
+    }
+}
+
+/// Extension of the file type from the first bytes of the file,
+/// zip and compound files are opened to tell the documents (docx, epub, doc...) from the archives
+pub fn detect_extension(file_path: &str) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_LENGTH as usize);
+    File::open(file_path).ok()?.take(SNIFF_LENGTH).read_to_end(&mut head).ok()?;
+    match sniff(&head)? {
+        "zip" => Some(zip_extension(file_path, &head)),
+        "ole" => ole_extension(file_path),
+        extension => Some(extension),
+    }
+}
+
+/// Type of the content from its signature: pdf, zip, ole (compound file), mkv, webm, mp4, mov, m4a,
+/// avi, wav, webp, jpg, png, gif, heic, avif, flac, ogg, ogv, mp3, mpg, ts, wmv, flv
+pub fn sniff(head: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, signature: &[u8]| head.get(offset..).is_some_and(|bytes| bytes.starts_with(signature));
+    if at(0, b"PK\x03\x04") {
+        return Some("zip");
+    }
+    if at(0, &CFB_SIGNATURE) {
+        return Some("ole");
+    }
+    // Some pdf writers add binary bytes before the header
+    if let Some(start) = head[..head.len().min(1024)].windows(5).position(|window| window == b"%PDF-") {
+        if head[..start].iter().all(|b| !b.is_ascii() || b.is_ascii_control()) {
+            return Some("pdf");
+        }
+    }
+    if at(0, b"\x1a\x45\xdf\xa3") {
+        let doc_type = &head[..head.len().min(64)];
+        return Some(if doc_type.windows(4).any(|window| window == b"webm") { "webm" } else { "mkv" });
+    }
+    if at(4, b"ftyp") {
+        return Some(match head.get(8..12).unwrap_or_default() {
+            b"M4A " | b"M4B " | b"M4P " => "m4a",
+            b"qt  " => "mov",
+            b"heic" | b"heix" | b"mif1" | b"msf1" => "heic",
+            b"avif" | b"avis" => "avif",
+            _ => "mp4",
+        });
+    }
+    if at(0, b"RIFF") {
+        return match head.get(8..12).unwrap_or_default() {
+            b"AVI " => Some("avi"),
+            b"WAVE" => Some("wav"),
+            b"WEBP" => Some("webp"),
+            _ => None,
+        };
+    }
+    if at(0, b"OggS") {
+        return Some(if head.windows(7).any(|window| window == b"\x80theora") { "ogv" } else { "ogg" });
+    }
+    let signatures: [(&[u8], &'static str); 8] = [
+        (b"\xff\xd8\xff", "jpg"),
+        (b"\x89PNG\r\n\x1a\n", "png"),
+        (b"GIF87a", "gif"),
+        (b"GIF89a", "gif"),
+        (b"fLaC", "flac"),
+        (b"\x00\x00\x01\xba", "mpg"),
+        (b"\x30\x26\xb2\x75\x8e\x66\xcf\x11", "wmv"),
+        (b"FLV\x01", "flv"),
+    ];
+    if let Some((_, extension)) = signatures.iter().find(|(signature, _)| at(0, signature)) {
+        return Some(extension);
+    }
+    // ID3 tag (version 2 to 4), or MPEG audio frame without tag
+    if (at(0, b"ID3") && matches!(head.get(3), Some(2..=4))) || (head.len() >= 2 && head[0] == 0xff && matches!(head[1], 0xfb | 0xf3 | 0xf2)) {
+        return Some("mp3");
+    }
+    // Transport stream: sync byte every 188 bytes, text files have no NUL byte
+    if head.len() >= TS_PACKET_SIZE * 4 && (0..4).all(|i| head[i * TS_PACKET_SIZE] == 0x47) && head.contains(&0) {
+        return Some("ts");
+    }
+    None
+}
+
+/// epub, odt, ods, odp from the mimetype entry, docx, xlsx, pptx from their main part, zip otherwise
+fn zip_extension(file_path: &str, head: &[u8]) -> &'static str {
+    // Local header: name length at 26, extra length at 28, name at 30 then the extra field and the content
+    let name_length = u16::from_le_bytes([head.get(26).copied().unwrap_or_default(), head.get(27).copied().unwrap_or_default()]) as usize;
+    let extra_length = u16::from_le_bytes([head.get(28).copied().unwrap_or_default(), head.get(29).copied().unwrap_or_default()]) as usize;
+    if head.get(30..30 + name_length) == Some(b"mimetype") {
+        let content = head.get(30 + name_length + extra_length..).unwrap_or_default();
+        if let Some((_, extension)) = ZIP_MIMETYPES.iter().find(|(mimetype, _)| content.starts_with(mimetype.as_bytes())) {
+            return extension;
+        }
+    }
+
+    // The extension is trusted if it is an Office Open XML one, to avoid reading the zip directory
+    let extension = get_extension(file_path).to_lowercase();
+    if let Some((_, extension)) = OOXML_PARTS.iter().find(|(_, ooxml)| *ooxml == extension) {
+        return extension;
+    }
+    let names: Vec<String> = File::open(file_path)
+        .ok()
+        .and_then(|file| ZipArchive::new(file).ok())
+        .map(|archive| archive.file_names().map(String::from).collect())
+        .unwrap_or_default();
+    OOXML_PARTS
+        .iter()
+        .find(|(part, _)| names.iter().any(|name| name == part))
+        .map_or("zip", |(_, extension)| extension)
+}
+
+/// doc, xls or ppt from the streams of the compound file (its directory only), None for the other ones (msi, msg...)
+fn ole_extension(file_path: &str) -> Option<&'static str> {
+    let extension = get_extension(file_path).to_lowercase();
+    if let Some(extension) = ["doc", "xls", "ppt"].into_iter().find(|ole| *ole == extension) {
+        return Some(extension);
+    }
+    let streams = stream_names(file_path).ok()?;
+    [("WordDocument", "doc"), ("Workbook", "xls"), ("Book", "xls"), ("PowerPoint Document", "ppt")]
+        .into_iter()
+        .find(|(stream, _)| streams.iter().any(|name| name == stream))
+        .map(|(_, extension)| extension)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{fs, io::Write};
+
+    #[test]
+    fn magic_sniff() {
+        assert_eq!(Some("pdf"), sniff(b"%PDF-1.7\n"));
+        assert_eq!(Some("png"), sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert_eq!(Some("jpg"), sniff(b"\xff\xd8\xff\xe0\0\x10JFIF"));
+        assert_eq!(Some("mkv"), sniff(b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x88matroska"));
+        assert_eq!(Some("webm"), sniff(b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x84webm"));
+        assert_eq!(Some("mp4"), sniff(b"\0\0\0\x20ftypisom\0\0\x02\0"));
+        assert_eq!(Some("m4a"), sniff(b"\0\0\0\x20ftypM4A \0\0\x02\0"));
+        assert_eq!(Some("avi"), sniff(b"RIFF\x10\0\0\0AVI LIST"));
+        assert_eq!(Some("wav"), sniff(b"RIFF\x10\0\0\0WAVEfmt "));
+        assert_eq!(Some("flac"), sniff(b"fLaC\0\0\0\x22"));
+        assert_eq!(Some("ogg"), sniff(b"OggS\0\x02\0\0\0\0\0\0\0\0\x01vorbis"));
+        assert_eq!(Some("ogv"), sniff(b"OggS\0\x02\0\0\0\0\0\0\0\0\x80theora"));
+        assert_eq!(Some("zip"), sniff(b"PK\x03\x04\x14\0"));
+        assert_eq!(Some("mp3"), sniff(b"ID3\x04\0\0\0\0\x01\x02"));
+        assert_eq!(None, sniff(b"ID3 tags are read by the audio handler"));
+        assert_eq!(None, sniff(b"A text about the %PDF- header"));
+        assert_eq!(None, sniff(b"Plain text, starting with G"));
+        assert_eq!(None, sniff(b""));
+    }
+
+    #[test]
+    fn magic_detect_extension() {
+        let dir = std::env::temp_dir().join(format!("oms_magic_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // A docx and an epub without extension, a zip
+        let write_zip = |name: &str, entries: &[(&str, &str)]| {
+            let path = dir.join(name);
+            let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+            for (entry, content) in entries {
+                let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+                writer.start_file(*entry, options).unwrap();
+                writer.write_all(content.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+            path.display().to_string()
+        };
+        let docx = write_zip("report", &[("[Content_Types].xml", "<Types/>"), ("word/document.xml", "<w:document/>")]);
+        let epub = write_zip("book.zip", &[("mimetype", "application/epub+zip"), ("content.opf", "<package/>")]);
+        let archive = write_zip("delivery", &[("notes.txt", "notes")]);
+        assert_eq!("docx", media_extension(&docx));
+        assert_eq!("epub", media_extension(&epub));
+        assert_eq!("zip", media_extension(&archive));
+
+        // Wrong extension
+        let movie = dir.join("movie.txt");
+        fs::write(&movie, b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01").unwrap();
+        assert_eq!("mkv", media_extension(&movie.display().to_string()));
+        let text = dir.join("notes.md");
+        fs::write(&text, "# Notes").unwrap();
+        assert_eq!("md", media_extension(&text.display().to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::{io::{Error, ErrorKind}, path::Path};
+use regex::Regex;
+use super::walk::{walk, WalkOption};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Prefix of a pattern used as a regular expression
+pub const REGEX_PREFIX: &str = "re:";
+
+/// File name pattern: a glob (`*.min.js`, `**/build/**`, `IMG_????.{jpg,png}`),
+/// or a regular expression prefixed by `re:` (`re:^\d{4}-`)
+///
+/// Patterns are case insensitive, and match the file name or its path relative to the searched directory
+///
+/// # Examples
+///
+/// ```
+/// use oms::helpers::file::pattern::FilePattern;
+///
+/// let pattern = FilePattern::new("**/build/**").unwrap();
+/// assert!(pattern.is_match("/home/me/project/src/build/app.js", "/home/me/project"));
+/// assert!(!pattern.is_match("/home/me/build/project/app.js", "/home/me/build/project"));
+///
+/// let pattern = FilePattern::new("*.MIN.js").unwrap();
+/// assert!(pattern.is_match("lib/jquery.min.js", ""));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilePattern {
+    regex: Regex,
+}
+
+impl FilePattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(expression) => Self::from_regex(expression, pattern),
+            None => Self::from_regex(&glob_to_regex(pattern), pattern),
+        }
+    }
+
+    /// Exact file name, wildcard chars are not interpreted
+    pub fn from_name(file_name: &str) -> Result<Self> {
+        Self::from_regex(&format!("^{}$", regex::escape(file_name)), file_name)
+    }
+
+    fn from_regex(expression: &str, pattern: &str) -> Result<Self> {
+        match Regex::new(&format!("(?i){expression}")) {
+            Ok(regex) => Ok(FilePattern { regex }),
+            Err(err) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid pattern {pattern}: {err}")
+            )),
+        }
+    }
+
+    /// Check the file name and the path relative to base_path (the whole path if base_path is empty)
+    pub fn is_match(&self, file_path: &str, base_path: &str) -> bool {
+        let relative_path = file_path
+            .strip_prefix(base_path)
+            .unwrap_or(file_path)
+            .trim_start_matches('/');
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        self.regex.is_match(file_name) || self.regex.is_match(relative_path)
+    }
+}
+
+/// Check if the value uses glob wildcards or is a regular expression
+pub fn is_pattern(value: &str) -> bool {
+    value.starts_with(REGEX_PREFIX) || value.contains(['*', '?', '[', '{'])
+}
+
+/// Parse patterns separated by separator, a separator inside {} is part of the glob
+pub fn patterns_from(value: &str, separator: char) -> Result<Vec<FilePattern>> {
+    let mut patterns = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in value.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            c if c == separator && depth <= 0 => {
+                patterns.push(std::mem::take(&mut current));
+                continue;
+            },
+            _ => (),
+        }
+        current.push(c);
+    }
+    patterns.push(current);
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(FilePattern::new)
+        .collect()
+}
+
+/// Files matching a glob (or regex) path, walking from the directory before the first wildcard:
+/// /media/films/**/*.mkv walks /media/films
+pub fn glob_files(pattern: &str, walk_option: &WalkOption) -> Result<Vec<String>> {
+    let base_path = if pattern.starts_with(REGEX_PREFIX) {
+        String::from(".")
+    } else {
+        let literal = &pattern[..pattern.find(['*', '?', '[', '{']).unwrap_or(pattern.len())];
+        match literal.rfind('/') {
+            Some(0) => String::from("/"),
+            Some(index) => literal[..index].to_string(),
+            None => String::from("."),
+        }
+    };
+    let matcher = FilePattern::new(pattern)?;
+    // A glob matches the whole path given, from the walked directory
+    let path_regex = match pattern.starts_with(REGEX_PREFIX) {
+        true => None,
+        false => {
+            let relative_pattern = pattern.strip_prefix(&base_path).unwrap_or(pattern).trim_start_matches('/');
+            Some(FilePattern::from_regex(&glob_to_regex(relative_pattern), pattern)?.regex)
+        },
+    };
+
+    let mut files = vec![];
+    if Path::new(&base_path).is_dir() {
+        walk(&base_path, walk_option, |file_path| {
+            let is_match = match &path_regex {
+                Some(regex) => regex.is_match(file_path.strip_prefix(&base_path).unwrap_or(&file_path).trim_start_matches('/')),
+                None => matcher.is_match(&file_path, &base_path),
+            };
+            if is_match {
+                files.push(file_path);
+            }
+        });
+    }
+    Ok(files)
+}
+
+/// ** matches any number of directories, * and ? any chars except /, {a,b} one of the alternatives
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = glob.chars().collect();
+    let mut index = 0;
+    let mut in_braces = false;
+    while index < chars.len() {
+        let c = chars[index];
+        match c {
+            '*' if chars.get(index + 1) == Some(&'*') => {
+                index += 1;
+                if chars.get(index + 1) == Some(&'/') {
+                    index += 1;
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            },
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[index..].iter().position(|c| *c == ']') {
+                Some(end) => {
+                    let class: String = chars[index + 1..index + end].iter().collect();
+                    let class = class.strip_prefix('!').map_or(class.clone(), |c| format!("^{c}"));
+                    regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                    index += end;
+                },
+                None => regex.push_str("\\["),
+            },
+            '{' => {
+                in_braces = true;
+                regex.push_str("(?:");
+            },
+            '}' if in_braces => {
+                in_braces = false;
+                regex.push(')');
+            },
+            ',' if in_braces => regex.push('|'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        index += 1;
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pattern_glob() {
+        let patterns = patterns_from("*.{jpg,png}, re:^\\d{4}-,photo_?.txt", ',').unwrap();
+        assert_eq!(3, patterns.len());
+        assert!(patterns[0].is_match("/photos/IMG.JPG", "/photos"));
+        assert!(!patterns[0].is_match("/photos/img.gif", "/photos"));
+        assert!(patterns[1].is_match("/notes/2024-01.txt", "/notes"));
+        assert!(patterns[2].is_match("photo_1.txt", ""));
+        assert!(!patterns[2].is_match("photo_12.txt", ""));
+
+        let pattern = FilePattern::new("src/[!t]*.rs").unwrap();
+        assert!(pattern.is_match("/project/src/main.rs", "/project"));
+        assert!(!pattern.is_match("/project/src/test.rs", "/project"));
+        assert!(!pattern.is_match("/project/lib/src/main.rs", "/project"));
+    }
+
+    #[test]
+    fn pattern_glob_files() {
+        let dir = std::env::temp_dir().join(format!("oms_pattern_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        for file in ["a.mkv", "b.txt", "sub/c.mkv"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        let dir_path = dir.display().to_string();
+        let names = |pattern: &str| -> Vec<String> {
+            let mut files: Vec<String> = glob_files(&format!("{dir_path}/{pattern}"), &WalkOption::new())
+                .unwrap()
+                .iter()
+                .map(|f| f.replace(&dir_path, ""))
+                .collect();
+            files.sort();
+            files
+        };
+        assert_eq!(vec!["/a.mkv"], names("*.mkv"));
+        assert_eq!(vec!["/a.mkv", "/sub/c.mkv"], names("**/*.mkv"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{fs, path::Path};
+use colored::Colorize;
+use ignore::{DirEntry, WalkBuilder};
+use crate::helpers::{cache, interrupt};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Ignore file specific to oms, same syntax as .gitignore
+pub const IGNORE_FILE_NAME: &str = ".omsignore";
+
+/// Options of the directory walker,
+/// shared by the commands walking through directories (search, info, transcode, renamemovie)
+#[derive(Clone)]
+pub struct WalkOption {
+    /// Files of the walked directory are at depth 1, None for unlimited
+    pub max_depth: Option<usize>,
+    /// Walk through hidden files and directories
+    pub hidden: bool,
+    /// Follow symbolic links to directories
+    pub follow_symlinks: bool,
+}
+
+impl WalkOption {
+    pub fn new() -> Self {
+        WalkOption {
+            max_depth: None,
+            hidden: false,
+            follow_symlinks: false,
+        }
+    }
+
+    pub fn set_max_depth(&mut self, value: &str) -> Result<()> {
+        match value.parse::<usize>() {
+            Ok(v) if v > 0 => {
+                self.max_depth = Some(v);
+                Ok(())
+            },
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid value for max-depth"
+            ))
+        }
+    }
+
+    pub fn set_hidden(&mut self) {
+        self.hidden = true;
+    }
+
+    pub fn set_follow_symlinks(&mut self) {
+        self.follow_symlinks = true;
+    }
+}
+
+impl Default for WalkOption {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Walk through the files of a directory and its sub directories, ordered by name
+///
+/// Skip the files ignored by .gitignore and .omsignore, hidden files (unless `hidden` is set),
+/// .git directories and the cache directory.
+/// Unreadable directories and symbolic link loops are reported as warnings.
+///
+/// # Arguments
+///
+/// * `dir_path` - the directory to walk through
+/// * `walk_option` - depth, hidden files, symbolic links
+/// * `callback` - called with the path of each file found
+pub fn walk<F>(dir_path: &str, walk_option: &WalkOption, mut callback: F)
+where
+    F: FnMut(String),
+{
+    let cache_path = fs::canonicalize(cache::get_base_path()).ok();
+    let walker = WalkBuilder::new(dir_path)
+        .hidden(!walk_option.hidden)
+        .follow_links(walk_option.follow_symlinks)
+        .max_depth(walk_option.max_depth)
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILE_NAME)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| !is_skipped_dir(entry, cache_path.as_deref()))
+        .build();
+
+    for entry in walker {
+        // Ctrl-C, when the command handles it
+        if interrupt::is_interrupted() {
+            break;
+        }
+        match entry {
+            // Symbolic links to files are always followed
+            Ok(entry) if entry.path().is_file() => callback(entry.path().display().to_string()),
+            Ok(_) => (),
+            Err(err) => eprintln!("{}", format!("Warning: {err}").yellow()),
+        }
+    }
+}
+
+fn is_skipped_dir(entry: &DirEntry, cache_path: Option<&Path>) -> bool {
+    if !entry.file_type().is_some_and(|t| t.is_dir()) {
+        return false;
+    }
+    if entry.file_name() == ".git" {
+        return true;
+    }
+    match (cache_path, fs::canonicalize(entry.path())) {
+        (Some(cache_path), Ok(path)) => path == cache_path,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn walk_test_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("oms_walk_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub/deep")).unwrap();
+        fs::create_dir_all(dir.join(".hidden")).unwrap();
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        for file in ["a.txt", "sub/b.txt", "sub/deep/c.txt", ".hidden/d.txt", "node_modules/e.js", "f.log"] {
+            fs::write(dir.join(file), "content").unwrap();
+        }
+        fs::write(dir.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(dir.join(IGNORE_FILE_NAME), "*.log\n").unwrap();
+        dir.display().to_string()
+    }
+
+    fn walked_files(dir: &str, walk_option: &WalkOption) -> Vec<String> {
+        let mut files = vec![];
+        walk(dir, walk_option, |file_path| files.push(file_path.replace(dir, "")));
+        files
+    }
+
+    #[test]
+    fn walk_ignore_files() {
+        let dir = walk_test_dir("ignore");
+        assert_eq!(vec!["/a.txt", "/sub/b.txt", "/sub/deep/c.txt"], walked_files(&dir, &WalkOption::new()));
+
+        let mut walk_option = WalkOption::new();
+        walk_option.set_hidden();
+        assert!(walked_files(&dir, &walk_option).contains(&"/.hidden/d.txt".to_string()));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn walk_max_depth() {
+        let dir = walk_test_dir("depth");
+        let mut walk_option = WalkOption::new();
+        walk_option.set_max_depth("2").unwrap();
+        assert_eq!(vec!["/a.txt", "/sub/b.txt"], walked_files(&dir, &walk_option));
+        assert!(walk_option.set_max_depth("0").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
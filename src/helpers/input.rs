@@ -14,8 +14,11 @@ pub fn read_line(message: &str) -> String {
     }
  }
  
-///
-pub fn parse_command_option(args: &Vec<String>) -> HashMap<String, String> {
+/// Options whose value is optional, only given with '=' (--fuzzy, --fuzzy=1): the next argument is not their value
+const OPTIONAL_VALUE_OPTIONS: [&str; 1] = ["fuzzy"];
+
+/// Options of the arguments: -abc, -a value, --name value, --name=value
+pub fn parse_command_option(args: &[String]) -> HashMap<String, String> {
     let mut options = HashMap::new();
     for (index, option) in args.iter().enumerate() {
         if option.starts_with("-") {
@@ -44,6 +47,8 @@ pub fn parse_command_option(args: &Vec<String>) -> HashMap<String, String> {
                         }
                     }
                     next_value = concated_value.join("=");
+                } else if OPTIONAL_VALUE_OPTIONS.contains(&option.as_str()) {
+                    next_value = String::new();
                 } else {
                     // Get next value as parameter of option
                     next_value = args.get(index + 1).unwrap_or(&String::new()).clone();
@@ -101,6 +106,15 @@ pub fn get_range_params(request_header: &Vec<String>, size: u64) -> Result<(u64,
 mod test {
     use super::*;
 
+    #[test]
+    fn parse_optional_value() {
+        let args: Vec<String> = ["oms", "search", "--fuzzy", "-t", "4", "/home/me"].iter().map(|arg| arg.to_string()).collect();
+        let options = parse_command_option(&args);
+        assert_eq!(Some(&String::new()), options.get("fuzzy"));
+        assert_eq!(Some(&String::from("4")), options.get("t"));
+        assert_eq!(Some(&String::from("1")), parse_command_option(&[String::from("--fuzzy=1")]).get("fuzzy"));
+    }
+
     #[test]
     fn get_range_params_0() {
         let headers = vec![
@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the first Ctrl-C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catch the first Ctrl-C (SIGINT) to let the running command stop cleanly,
+/// a second Ctrl-C stops the process at once
+pub fn handle_interrupt() {
+    unsafe {
+        libc::signal(libc::SIGINT, on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+}
+
+/// Check if Ctrl-C was pressed since handle_interrupt
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Stop the running command as Ctrl-C does (search -q after the first match)
+pub fn stop() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
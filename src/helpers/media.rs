@@ -3,7 +3,11 @@ use regex::Regex;
 use super::{file, ltrim, string};
 
 pub mod audio;
+pub mod epub;
 pub mod image;
+pub mod mail;
+pub mod markup;
+pub mod office;
 pub mod pdf;
 pub mod video;
 
@@ -17,6 +21,9 @@ pub fn normalize_media_title(title: &String) -> String {
     for extension in file::PDF_EXTENSIONS {
         title = ltrim(&title, &(".".to_owned() + extension));
     }
+    for extension in file::EPUB_EXTENSIONS {
+        title = ltrim(&title, &(".".to_owned() + extension));
+    }
     for extension in file::MS_EXTENSIONS {
         title = ltrim(&title, &(".".to_owned() + extension));
     }
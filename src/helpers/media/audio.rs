@@ -1,9 +1,9 @@
 use core::fmt;
-use std::io;
+use std::{collections::HashMap, io};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use sha256::digest;
-use crate::helpers::{string, command, file};
+use crate::helpers::{command, file};
 
 use super::normalize_media_title;
 
@@ -13,6 +13,13 @@ pub struct AudioResult {
     pub title: String,
     pub summary: String,
 
+    /// Artists, album artist and composer
+    pub casts: Vec<String>,
+    pub album: String,
+    pub track: u16,
+    pub year: u16,
+    pub genres: Vec<String>,
+
     pub thumb_url: String,
     pub poster_url: String,
 
@@ -31,23 +38,37 @@ pub struct AudioResult {
 impl fmt::Display for AudioResult {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut str = String::new();
-        str.push_str(&format!("File name: {}\n\n", self.title.bold()));
+        str.push_str(&format!("Title: {}", self.title.bold()));
+        if self.year > 0 {
+            str.push_str(&format!(" ({})", self.year));
+        }
+        str.push('\n');
+        str.push_str(&format!("\nArtists: {}\n", self.casts.join(", ")));
+        str.push_str(&format!("Album: {} (track {})\n", self.album, self.track));
+        str.push_str(&format!("Genre: {}\n", self.genres.join(", ")));
+        if !self.summary.is_empty() {
+            str.push_str(&format!("\n{}\n", self.summary));
+        }
 
         write!(f, "{str}")
     }
 }
 
 impl AudioResult {
-    pub fn search(&self, term: &String) -> Vec<(&str, String)> {
-        let mut result = vec![];
-
-        if string::text_contains(&self.full_path, term) {
-            result.push(("File", self.full_path.to_string()));
+    /// Searchable fields, labelled by name (Year only if known)
+    pub fn fields(&self) -> Vec<(&str, String)> {
+        let mut fields = vec![
+            ("File", self.full_path.to_string()),
+            ("Title", self.title.to_string()),
+            ("Artists", self.casts.join(", ")),
+            ("Album", self.album.to_string()),
+            ("Genres", self.genres.join(", ")),
+        ];
+        if self.year > 0 {
+            fields.push(("Year", self.year.to_string()));
         }
-        if string::text_contains(&self.title, term) {
-            result.push(("Title", self.title.to_string()));
-        }
-        return result;
+        fields.push(("Summary", self.summary.to_string()));
+        fields
     }
 }
 
@@ -59,6 +80,45 @@ pub fn audio_duration(file_path: &String) -> usize {
     return output.parse::<f64>().unwrap_or(0.).ceil() as usize;
 }
 
+/// Tags of the container (ID3, Vorbis comments, FLAC, MP4), from ffprobe format_tags and stream_tags
+/// Keys are lowercase, format tags win over stream tags (ogg only has stream tags)
+pub fn audio_tags(file_path: &String) -> HashMap<String, String> {
+    let output = command::exec(
+        "ffprobe",
+         ["-v", "quiet", "-print_format", "json", "-show_entries", "format_tags:stream_tags", "-i", file_path]
+    );
+    let mut tags = HashMap::new();
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&output) else {
+        return tags;
+    };
+    let format_tags = json["format"]["tags"].as_object().into_iter();
+    let stream_tags = json["streams"].as_array().into_iter().flatten().filter_map(|s| s["tags"].as_object());
+    for object in format_tags.chain(stream_tags) {
+        for (key, value) in object {
+            if let Some(value) = value.as_str().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+                tags.entry(key.to_lowercase()).or_insert(value.to_string());
+            }
+        }
+    }
+    tags
+}
+
+/// "Rock; Pop" -> list
+/// Artists are only split on ';' (AC/DC, Earth, Wind & Fire), genres also on ',' and '/'
+fn split_tag(value: Option<&String>, separators: &[char]) -> Vec<String> {
+    value
+        .map(|v| v.split(separators).map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// "03/12" -> 3, "2004-05-01" -> 2004
+fn number_tag(value: Option<&String>) -> u16 {
+    value
+        .map(|v| v.trim().chars().take_while(|c| c.is_ascii_digit()).take(4).collect::<String>())
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or_default()
+}
+
 pub fn get_audio_result(base_path: &String, file_path: &String) -> Result<AudioResult, io::Error> {
     let file_size: usize = file::file_size(file_path).unwrap_or_default() as usize;
     let file_name = file::get_file_name(file_path);
@@ -66,10 +126,24 @@ pub fn get_audio_result(base_path: &String, file_path: &String) -> Result<AudioR
 
     let hash = file::sha256(file_path).unwrap_or(digest(&relative_file_path));
     let file_duration = audio_duration(&file_path);
+    let tags = audio_tags(file_path);
+
+    let mut casts = split_tag(tags.get("artist"), &[';']);
+    for artist in split_tag(tags.get("album_artist"), &[';']).into_iter().chain(split_tag(tags.get("composer"), &[';'])) {
+        if !casts.contains(&artist) {
+            casts.push(artist);
+        }
+    }
 
     Ok(AudioResult {
-        title: normalize_media_title(&file_name),
-        summary: String::new(),
+        title: normalize_media_title(tags.get("title").unwrap_or(&file_name)),
+        summary: tags.get("comment").or(tags.get("description")).cloned().unwrap_or_default(),
+
+        casts,
+        album: tags.get("album").cloned().unwrap_or_default(),
+        track: number_tag(tags.get("track").or(tags.get("tracknumber"))),
+        year: number_tag(tags.get("date").or(tags.get("year")).or(tags.get("originaldate"))),
+        genres: split_tag(tags.get("genre"), &[';', ',', '/']),
 
         thumb_url: String::from("/assets/img/audio.png"),
         poster_url: String::from("/assets/img/audio.png"),
@@ -85,4 +159,18 @@ pub fn get_audio_result(base_path: &String, file_path: &String) -> Result<AudioR
         duration: file_duration,
         file_size: file_size,
     })    
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn audio_tag_values() {
+        assert_eq!(3, number_tag(Some(&"03/12".to_string())));
+        assert_eq!(2004, number_tag(Some(&"2004-05-01".to_string())));
+        assert_eq!(0, number_tag(None));
+        assert_eq!(vec!["AC/DC", "Queen"], split_tag(Some(&"AC/DC; Queen".to_string()), &[';']));
+        assert_eq!(vec!["Rock", "Pop"], split_tag(Some(&"Rock/Pop".to_string()), &[';', ',', '/']));
+    }
+}
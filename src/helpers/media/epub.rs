@@ -0,0 +1,371 @@
+use core::fmt;
+use std::{collections::HashMap, fs::File, io::{self, BufReader, Read}, path::{Component, Path}};
+
+use colored::Colorize;
+use quick_xml::{escape::resolve_predefined_entity, events::{BytesStart, Event}, Reader};
+use serde::{Deserialize, Serialize};
+use sha256::digest;
+use zip::ZipArchive;
+use crate::helpers::file;
+
+use super::normalize_media_title;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+type Archive = ZipArchive<BufReader<File>>;
+
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EpubResult {
+    pub title: String,
+    pub summary: String,
+
+    pub year: u16,
+    pub genres: Vec<String>,
+    pub casts: Vec<String>,
+
+    pub provider: String,
+
+    pub rating: f32,
+    pub file_type: String,
+    pub file_path: String,
+    pub full_path: String,
+    pub hash: String,
+    pub modification_time: u64,
+    pub duration: usize,
+    pub file_size: usize,
+}
+
+/// A content document of the spine
+pub struct EpubChapter {
+    /// First heading of the chapter, or its file name
+    pub title: String,
+    pub content: String,
+}
+
+/// Dublin Core metadata and reading order of the package document (OPF)
+#[derive(Default)]
+struct Package {
+    title: String,
+    creators: Vec<String>,
+    subjects: Vec<String>,
+    description: String,
+    date: String,
+    /// Paths of the spine content documents in the archive
+    spine: Vec<String>,
+}
+
+impl fmt::Display for EpubResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut str = String::new();
+        str.push_str(&format!("Title: {}", self.title.bold()));
+        if self.year > 0 {
+            str.push_str(&format!(" ({})", self.year));
+        }
+        str.push('\n');
+
+        str.push_str(&format!("\n{}\n", self.summary));
+        str.push_str(&format!("\nAuthors: {}\n", self.casts.join(", ")));
+        str.push_str(&format!("\nGenre: {}\n", self.genres.join(", ")));
+
+        write!(f, "{str}")
+    }
+}
+
+impl EpubResult {
+    /// Searchable fields, labelled by name
+    pub fn fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("File", self.full_path.to_string()),
+            ("Title", self.title.to_string()),
+            ("Summary", self.summary.to_string()),
+            ("Authors", self.casts.join(", ")),
+            ("Genres", self.genres.join(", ")),
+        ]
+    }
+}
+
+pub fn get_epub_result(base_path: &String, file_path: &String) -> Result<EpubResult> {
+    let file_size: usize = file::file_size(file_path).unwrap_or_default() as usize;
+    let relative_file_path = file_path.replace(base_path, "");
+
+    let hash = file::sha256(file_path).unwrap_or(digest(&relative_file_path));
+
+    let mut archive = open_archive(file_path)?;
+    let package = read_package(&mut archive)?;
+    let title = if package.title.is_empty() { file::get_file_name(file_path) } else { package.title };
+
+    Ok(EpubResult {
+        title: normalize_media_title(&title),
+        summary: package.description,
+        casts: package.creators,
+        genres: package.subjects,
+        year: package.date.get(..4).unwrap_or_default().parse::<u16>().unwrap_or_default(),
+
+        provider: String::from("local"),
+
+        rating: 1.,
+        file_type: String::from("epub"),
+        file_path: relative_file_path,
+        full_path: file_path.to_string(),
+        hash,
+        modification_time: file::get_creation_time(file_path),
+        duration: 0,
+        file_size,
+    })
+}
+
+/// Text of the content documents, in the reading order of the spine
+pub fn get_epub_chapters(file_path: &str) -> Result<Vec<EpubChapter>> {
+    let mut archive = open_archive(file_path)?;
+    let package = read_package(&mut archive)?;
+    let mut chapters = vec![];
+    for path in &package.spine {
+        // Missing documents are skipped, the others are still readable
+        if let Ok(xhtml) = read_entry(&mut archive, path) {
+            let (heading, content) = xhtml_to_text(&xhtml);
+            chapters.push(EpubChapter {
+                title: heading.unwrap_or_else(|| file::get_file_name(&path.to_string())),
+                content,
+            });
+        }
+    }
+    Ok(chapters)
+}
+
+fn open_archive(file_path: &str) -> Result<Archive> {
+    let file = File::open(file_path)?;
+    Ok(ZipArchive::new(BufReader::new(file))?)
+}
+
+fn read_entry(archive: &mut Archive, path: &str) -> Result<String> {
+    let mut entry = archive.by_name(path)?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// META-INF/container.xml gives the path of the OPF file
+fn read_package(archive: &mut Archive) -> Result<Package> {
+    let container = read_entry(archive, "META-INF/container.xml")?;
+    let opf_path = find_attribute(&container, b"rootfile", "full-path").ok_or(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Invalid epub: rootfile not found in container.xml"
+    ))?;
+    let opf = read_entry(archive, &opf_path)?;
+    let opf_dir = Path::new(&opf_path).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    Ok(package_from_opf(&opf, &opf_dir))
+}
+
+fn package_from_opf(opf: &str, opf_dir: &Path) -> Package {
+    let mut reader = Reader::from_str(opf);
+    let mut package = Package::default();
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine_ids = vec![];
+    let mut current = vec![];
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => current = local_name(&e),
+            Ok(Event::Empty(e)) => match local_name(&e).as_slice() {
+                b"item" => if let (Some(id), Some(href)) = (attribute(&e, "id"), attribute(&e, "href")) {
+                    manifest.insert(id, href);
+                },
+                b"itemref" => if let Some(idref) = attribute(&e, "idref") {
+                    spine_ids.push(idref);
+                },
+                _ => (),
+            },
+            Ok(Event::End(_)) => current.clear(),
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().unwrap_or_default().trim().to_string();
+                match current.as_slice() {
+                    b"title" if package.title.is_empty() => package.title = text,
+                    b"creator" => package.creators.push(text),
+                    b"subject" => package.subjects.push(text),
+                    b"description" => package.description = text,
+                    b"date" if package.date.is_empty() => package.date = text,
+                    _ => (),
+                }
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => (),
+        }
+    }
+    package.spine = spine_ids
+        .iter()
+        .filter_map(|id| manifest.get(id))
+        .map(|href| {
+            let href = urlencoding::decode(href).map(|h| h.to_string()).unwrap_or(href.to_string());
+            entry_path(&opf_dir.join(href))
+        })
+        .collect();
+    package
+}
+
+/// Path of an entry of the archive, with the . and .. of the relative hrefs resolved (OEBPS/text/../images/a.png)
+fn entry_path(path: &Path) -> String {
+    let mut names: Vec<String> = vec![];
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => names.push(name.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                names.pop();
+            },
+            _ => (),
+        }
+    }
+    names.join("/")
+}
+
+/// Text of a XHTML content document, and its first heading
+/// Block elements are separated by a new line, script and style are skipped
+fn xhtml_to_text(xhtml: &str) -> (Option<String>, String) {
+    let mut reader = Reader::from_str(xhtml);
+    reader.config_mut().check_end_names = false;
+    let mut content = String::new();
+    let mut heading: Option<String> = None;
+    let mut in_heading = false;
+    let mut in_body = false;
+    let mut skip_depth = 0;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match local_name(&e).as_slice() {
+                _ if skip_depth > 0 => skip_depth += 1,
+                b"script" | b"style" => skip_depth = 1,
+                b"body" => in_body = true,
+                name if is_heading(name) => {
+                    in_heading = heading.is_none();
+                    content.push('\n');
+                },
+                name if is_block(name) => content.push('\n'),
+                _ => (),
+            },
+            Ok(Event::End(e)) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                let name = e.local_name();
+                if is_heading(name.as_ref()) {
+                    in_heading = false;
+                }
+                if is_block(name.as_ref()) {
+                    content.push('\n');
+                }
+            },
+            Ok(Event::Empty(e)) if matches!(local_name(&e).as_slice(), b"br" | b"hr") => content.push('\n'),
+            Ok(Event::Text(e)) if in_body && skip_depth == 0 => {
+                let text = e
+                    .unescape_with(html_entity)
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&e).to_string());
+                let text = text.split_ascii_whitespace().collect::<Vec<&str>>().join(" ");
+                if in_heading && !text.is_empty() {
+                    heading = Some(match heading {
+                        Some(h) => format!("{h} {text}"),
+                        None => text.clone(),
+                    });
+                }
+                if !text.is_empty() && !content.ends_with(['\n', ' ']) {
+                    content.push(' ');
+                }
+                content.push_str(&text);
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => (),
+        }
+    }
+    let content = content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    (heading, content)
+}
+
+fn is_heading(name: &[u8]) -> bool {
+    matches!(name, b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6")
+}
+
+fn is_block(name: &[u8]) -> bool {
+    is_heading(name) || matches!(
+        name,
+        b"p" | b"div" | b"section" | b"article" | b"blockquote" | b"li" | b"tr" | b"td" | b"th" | b"pre"
+            | b"dt" | b"dd" | b"figcaption"
+    )
+}
+
+/// HTML entities usually found in ebooks (XHTML only defines the XML ones)
+fn html_entity(entity: &str) -> Option<&'static str> {
+    match entity {
+        "nbsp" => Some("\u{a0}"),
+        "shy" => Some(""),
+        "mdash" => Some("—"),
+        "ndash" => Some("–"),
+        "hellip" => Some("…"),
+        "lsquo" => Some("‘"),
+        "rsquo" => Some("’"),
+        "ldquo" => Some("“"),
+        "rdquo" => Some("”"),
+        "laquo" => Some("«"),
+        "raquo" => Some("»"),
+        "copy" => Some("©"),
+        _ => resolve_predefined_entity(entity),
+    }
+}
+
+/// Element name without namespace prefix (dc:title -> title)
+fn local_name(e: &BytesStart) -> Vec<u8> {
+    e.local_name().as_ref().to_vec()
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|a| a.unescape_value().ok().map(|v| v.to_string()))
+}
+
+fn find_attribute(xml: &str, element: &[u8], name: &str) -> Option<String> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if local_name(&e) == element => return attribute(&e, name),
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn epub_package() {
+        let opf = r#"<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title>Vingt mille lieues</dc:title><dc:creator>Jules Verne</dc:creator><dc:subject>Aventure</dc:subject><dc:date>1870-01-01</dc:date>
+</metadata><manifest><item id="c1" href="text/chap%201.xhtml"/><item id="c2" href="text/chap2.xhtml"/></manifest>
+<spine><itemref idref="c2"/><itemref idref="c1"/></spine></package>"#;
+        let package = package_from_opf(opf, Path::new("OEBPS"));
+        assert_eq!("Vingt mille lieues", package.title);
+        assert_eq!(vec!["Jules Verne"], package.creators);
+        assert_eq!(vec!["Aventure"], package.subjects);
+        assert_eq!(vec!["OEBPS/text/chap2.xhtml", "OEBPS/text/chap 1.xhtml"], package.spine);
+
+        let opf = r#"<package><manifest><item id="c1" href="../Text/./cover.xhtml"/></manifest><spine><itemref idref="c1"/></spine></package>"#;
+        assert_eq!(vec!["Text/cover.xhtml"], package_from_opf(opf, Path::new("OEBPS")).spine);
+    }
+
+    #[test]
+    fn epub_xhtml_to_text() {
+        let xhtml = r#"<html><head><title>T</title><style>p {}</style></head><body>
+<h1>Chapitre <em>I</em></h1><p>Un&nbsp;écueil
+fuyant<br/>fin</p><script>var a;</script><p>Suite &amp; fin</p></body></html>"#;
+        let (heading, content) = xhtml_to_text(xhtml);
+        assert_eq!(Some("Chapitre I".to_string()), heading);
+        assert_eq!("Chapitre I\nUn\u{a0}écueil fuyant\nfin\nSuite & fin", content);
+    }
+}
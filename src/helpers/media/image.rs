@@ -4,7 +4,7 @@ use std::io;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use sha256::digest;
-use crate::helpers::{command, file, output::draw_image, string};
+use crate::helpers::{command, file, output::draw_image};
 
 use super::normalize_media_title;
 
@@ -38,22 +38,14 @@ impl fmt::Display for ImageResult {
 }
 
 impl ImageResult {
-    pub fn search(&self, term: &String) -> Vec<(&str, String)> {
-        let mut result = vec![];
-
-        if string::text_contains(&self.full_path, term) {
-            result.push(("File", self.full_path.to_string()));
-        }
-        if string::text_contains(&self.title, term) {
-            result.push(("Title", self.title.to_string()));
-        }
-        if string::text_contains(&self.summary, term) {
-            result.push(("Summary", self.summary.to_string()));
-        }
-        if string::text_contains(&self.content, term) {
-            result.push(("Content", self.content.to_string()));
-        }
-        return result;
+    /// Searchable fields, labelled by name
+    pub fn fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("File", self.full_path.to_string()),
+            ("Title", self.title.to_string()),
+            ("Summary", self.summary.to_string()),
+            ("Content", self.content.to_string()),
+        ]
     }
 }
 
@@ -69,7 +61,7 @@ pub fn get_image_result(base_path: &String, file_path: &String) -> Result<ImageR
     Ok(ImageResult {
         title: normalize_media_title(&file_name),
         summary: String::new(),
-        content: command::exec("tesseract",[file_path, "-", "--oem", "1"]),
+        content: ocr(file_path),
 
         provider: String::from("local"),
 
@@ -82,4 +74,9 @@ pub fn get_image_result(base_path: &String, file_path: &String) -> Result<ImageR
         duration: 0,
         file_size: file_size,
     })    
-}
\ No newline at end of file
+}
+
+/// Text of an image read by tesseract, empty if tesseract is not installed
+pub fn ocr(file_path: &str) -> String {
+    command::exec("tesseract", [file_path, "-", "--oem", "1"])
+}
@@ -0,0 +1,257 @@
+pub mod mime;
+
+use std::{fs::File, io::{self, BufRead, BufReader}};
+use chrono::DateTime;
+use super::markup::html_to_text;
+use self::mime::{decode_charset, decode_transfer, decode_words, header, parameter, parameters, split_message, split_multipart};
+
+/// Nested multiparts and attached messages read, deeper parts are ignored
+const MAX_PART_DEPTH: usize = 10;
+
+/// A message of an eml or mbox file
+#[derive(Debug, Default)]
+pub struct MailMessage {
+    pub subject: String,
+    pub from: String,
+    pub to: String,
+    pub cc: String,
+    /// Date as 2024-06-03 10:00, or as written if it can't be parsed
+    pub date: String,
+    /// Text of the body parts (html only without plain text alternative) and of the text attachments
+    pub text: String,
+    /// Other attachments (pdf, office...), to be read by their extractor
+    pub attachments: Vec<MailAttachment>,
+}
+
+#[derive(Debug)]
+pub struct MailAttachment {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+impl MailMessage {
+    /// Header lines searched with the body: From, To, Cc, Subject, Date, Attachments
+    pub fn header_lines(&self) -> String {
+        let mut lines = vec![];
+        for (name, value) in [("From", &self.from), ("To", &self.to), ("Cc", &self.cc), ("Subject", &self.subject), ("Date", &self.date)] {
+            if !value.is_empty() {
+                lines.push(format!("{name}: {value}"));
+            }
+        }
+        if !self.attachments.is_empty() {
+            let names: Vec<&str> = self.attachments.iter().map(|attachment| attachment.name.as_str()).collect();
+            lines.push(format!("Attachments: {}", names.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Messages of an eml file (one message) or of a mbox file, parsed as they are read
+pub fn read_messages(file_path: &str) -> Result<Messages<BufReader<File>>, io::Error> {
+    Ok(Messages::new(BufReader::new(File::open(file_path)?)))
+}
+
+/// Messages read one by one: the whole content for an eml, a message from each "From " line for a mbox
+/// (">From " lines of the bodies are unescaped), only one message is in memory
+pub struct Messages<R: BufRead> {
+    reader: R,
+    /// Known from the first line: the file starts with "From "
+    is_mbox: Option<bool>,
+    /// A message is started, the next "From " line ends it
+    in_message: bool,
+    previous_empty: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Messages<R> {
+    pub fn new(reader: R) -> Self {
+        Messages {
+            reader,
+            is_mbox: None,
+            in_message: false,
+            previous_empty: true,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Messages<R> {
+    type Item = Result<MailMessage, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut message = vec![];
+        let mut line = vec![];
+        loop {
+            line.clear();
+            match self.reader.read_until(b'\n', &mut line) {
+                Ok(0) => {
+                    self.done = true;
+                    return self.in_message.then(|| Ok(parse_message(&message)));
+                },
+                Ok(_) => (),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                },
+            }
+            let is_mbox = *self.is_mbox.get_or_insert_with(|| line.starts_with(b"From "));
+            let separator = is_mbox && self.previous_empty && line.starts_with(b"From ");
+            self.previous_empty = line.trim_ascii().is_empty();
+            if separator {
+                if self.in_message {
+                    return Some(Ok(parse_message(&message)));
+                }
+                self.in_message = true;
+            } else if !is_mbox {
+                self.in_message = true;
+                message.extend_from_slice(&line);
+            } else if self.in_message {
+                let quotes = line.iter().take_while(|b| **b == b'>').count();
+                if quotes > 0 && line[quotes..].starts_with(b"From ") {
+                    message.extend_from_slice(&line[1..]);
+                } else {
+                    message.extend_from_slice(&line);
+                }
+            }
+        }
+    }
+}
+
+/// Headers, text parts and attachments of a RFC 5322 message
+pub fn parse_message(bytes: &[u8]) -> MailMessage {
+    let (headers, body) = split_message(bytes);
+    let value = |name: &str| decode_words(header(&headers, name).unwrap_or_default());
+    let date = header(&headers, "date").unwrap_or_default();
+    let mut message = MailMessage {
+        subject: value("subject"),
+        from: value("from"),
+        to: value("to"),
+        cc: value("cc"),
+        date: DateTime::parse_from_rfc2822(date)
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or(date.to_string()),
+        ..Default::default()
+    };
+    read_part(&headers, body, &mut message, 0);
+    message.text = message.text.trim().to_string();
+    message
+}
+
+/// Add the text of a part to the message, or its attachments
+fn read_part(headers: &[(String, String)], body: &[u8], message: &mut MailMessage, depth: usize) {
+    if depth > MAX_PART_DEPTH {
+        return;
+    }
+    let (content_type, type_parameters) = parameters(header(headers, "content-type").unwrap_or("text/plain"));
+    let (disposition, disposition_parameters) = parameters(header(headers, "content-disposition").unwrap_or_default());
+    let name = parameter(&disposition_parameters, "filename")
+        .or(parameter(&type_parameters, "name"))
+        .map(decode_words)
+        .unwrap_or_default();
+
+    if content_type.starts_with("multipart/") {
+        let boundary = parameter(&type_parameters, "boundary").unwrap_or_default();
+        let parts: Vec<_> = split_multipart(body, boundary).into_iter().map(split_message).collect();
+        // Alternatives of the same text: the plain text one if any
+        let plain = parts.iter().find(|(headers, _)| {
+            parameters(header(headers, "content-type").unwrap_or("text/plain")).0 == "text/plain"
+        });
+        match plain {
+            Some((headers, body)) if content_type == "multipart/alternative" => read_part(headers, body, message, depth + 1),
+            _ => for (headers, body) in &parts {
+                read_part(headers, body, message, depth + 1);
+            },
+        }
+        return;
+    }
+
+    let content = decode_transfer(body, header(headers, "content-transfer-encoding").unwrap_or_default());
+    if content_type == "message/rfc822" {
+        let attached = parse_message(&content);
+        message.text.push_str(&format!("\n{}\n\n{}\n", attached.header_lines(), attached.text));
+        message.attachments.extend(attached.attachments);
+        return;
+    }
+    if content_type.starts_with("text/") {
+        let text = decode_charset(&content, parameter(&type_parameters, "charset").unwrap_or("utf-8"));
+        let text = if content_type == "text/html" { html_to_text(&text) } else { text };
+        if !name.is_empty() {
+            message.text.push_str(&format!("\nAttachment: {name}\n"));
+        }
+        message.text.push_str(text.trim_end());
+        message.text.push('\n');
+    } else if disposition == "attachment" || !name.is_empty() {
+        message.attachments.push(MailAttachment { name, content });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mail_mbox() {
+        let mbox = b"From alice@example.com Mon Jun  3 10:00:00 2024
+From: Alice <alice@example.com>
+To: team@example.com
+Subject: =?utf-8?Q?Budget_r=C3=A9vis=C3=A9?=
+Date: Mon, 3 Jun 2024 10:00:00 +0200
+Content-Type: multipart/mixed; boundary=\"mixed\"
+
+--mixed
+Content-Type: multipart/alternative; boundary=alt
+
+--alt
+Content-Type: text/plain; charset=iso-8859-1
+Content-Transfer-Encoding: quoted-printable
+
+Le budget r=E9vis=E9 est joint.
+>From now on, use this one.
+--alt
+Content-Type: text/html
+
+<p>Le budget <b>r\xc3\xa9vis\xc3\xa9</b></p>
+--alt--
+--mixed
+Content-Type: application/pdf; name=\"budget.pdf\"
+Content-Transfer-Encoding: base64
+
+JVBERi0=
+--mixed
+Content-Type: text/csv
+Content-Disposition: attachment; filename=lines.csv
+
+total;42
+--mixed--
+
+From bob@example.com Tue Jun  4 09:00:00 2024
+From: Bob <bob@example.com>
+Subject: Re: Budget
+Content-Type: text/html; charset=utf-8
+
+<html><head><style>p {}</style></head><body><p>OK &amp; thanks</p></body></html>
+";
+        let messages: Vec<MailMessage> = Messages::new(&mbox[..]).collect::<io::Result<Vec<MailMessage>>>().unwrap();
+        assert_eq!(2, messages.len());
+        let first = &messages[0];
+        assert_eq!("Budget révisé", first.subject);
+        assert_eq!("Alice <alice@example.com>", first.from);
+        assert_eq!("2024-06-03 10:00", first.date);
+        assert_eq!("Le budget révisé est joint.\nFrom now on, use this one.\n\nAttachment: lines.csv\ntotal;42", first.text);
+        assert_eq!(1, first.attachments.len());
+        assert_eq!("budget.pdf", first.attachments[0].name);
+        assert_eq!(b"%PDF-".to_vec(), first.attachments[0].content);
+        assert!(first.header_lines().ends_with("Date: 2024-06-03 10:00\nAttachments: budget.pdf"));
+
+        assert_eq!("Re: Budget", messages[1].subject);
+        assert_eq!("OK & thanks", messages[1].text);
+
+        let eml = b"Subject: Hello\r\n\r\nFrom here on, one message\r\n\r\nFrom me\r\n";
+        let messages: Vec<MailMessage> = Messages::new(&eml[..]).collect::<io::Result<Vec<MailMessage>>>().unwrap();
+        assert_eq!(1, messages.len());
+        assert!(messages[0].text.ends_with("From me"));
+    }
+}
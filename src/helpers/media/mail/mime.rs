@@ -0,0 +1,223 @@
+use data_encoding::BASE64_NOPAD;
+use encoding_rs::{Encoding, UTF_8};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::helpers::file::encoding;
+
+/// RFC 2047 encoded word: =?charset?B|Q?text?=
+static ENCODED_WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"=\?([^?\s]+)\?([bBqQ])\?([^?\s]*)\?=").unwrap());
+
+/// Header names (lowercase) and unfolded values, and the body after the first empty line
+pub fn split_message(bytes: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let (header, body) = match find_empty_line(bytes) {
+        Some((end, body_start)) => (&bytes[..end], &bytes[body_start..]),
+        None => (bytes, &bytes[bytes.len()..]),
+    };
+    let mut headers: Vec<(String, String)> = vec![];
+    for line in encoding::decode(header).unwrap_or_default().lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+/// End of the header and start of the body
+fn find_empty_line(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for line in bytes.split_inclusive(|b| *b == b'\n') {
+        if line == b"\n" || line == b"\r\n" {
+            return Some((offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// First value of a header, by its lowercase name
+pub fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(header, _)| header == name).map(|(_, value)| value.as_str())
+}
+
+/// Lowercase value and parameters of a Content-Type or Content-Disposition header:
+/// text/plain; charset="utf-8" -> (text/plain, [(charset, utf-8)])
+pub fn parameters(value: &str) -> (String, Vec<(String, String)>) {
+    let mut items = value.split(';');
+    let main = items.next().unwrap_or_default().trim().to_lowercase();
+    let parameters = items
+        .filter_map(|item| item.split_once('='))
+        .map(|(name, value)| {
+            let name = name.trim().to_lowercase();
+            let value = value.trim().trim_matches('"');
+            // RFC 2231 extended value: charset'language'percent-encoded
+            match (name.strip_suffix('*'), value.splitn(3, '\'').collect::<Vec<&str>>().as_slice()) {
+                (Some(name), [charset, _, encoded]) => (name.to_string(), decode_charset(&percent_decode(encoded), charset)),
+                _ => (name, value.to_string()),
+            }
+        })
+        .collect();
+    (main, parameters)
+}
+
+pub fn parameter<'a>(parameters: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    parameters.iter().find(|(parameter, _)| parameter == name).map(|(_, value)| value.as_str())
+}
+
+/// Header value with its encoded words decoded,
+/// spaces between two encoded words are removed
+pub fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut last = 0;
+    for captures in ENCODED_WORD.captures_iter(value) {
+        let found = captures.get(0).unwrap();
+        let between = &value[last..found.start()];
+        if last == 0 || !between.trim().is_empty() {
+            decoded.push_str(between);
+        }
+        let text = captures[3].as_bytes();
+        let bytes = match &captures[2] {
+            "b" | "B" => decode_base64(text),
+            _ => decode_quoted_printable(&text.iter().map(|b| if *b == b'_' { b' ' } else { *b }).collect::<Vec<u8>>()),
+        };
+        // The charset may have a language: utf-8*en
+        decoded.push_str(&decode_charset(&bytes, captures[1].split('*').next().unwrap_or_default()));
+        last = found.end();
+    }
+    decoded.push_str(&value[last..]);
+    decoded
+}
+
+/// Body decoded from its Content-Transfer-Encoding, 7bit, 8bit and binary are kept as is
+pub fn decode_transfer(body: &[u8], transfer_encoding: &str) -> Vec<u8> {
+    match transfer_encoding.trim().to_lowercase().as_str() {
+        "base64" => decode_base64(body),
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    }
+}
+
+/// Base64 with line breaks, invalid content gives an empty result
+pub fn decode_base64(bytes: &[u8]) -> Vec<u8> {
+    let mut clean: Vec<u8> = bytes.iter().copied().filter(|b| !b.is_ascii_whitespace() && *b != b'=').collect();
+    // Incomplete last group
+    if clean.len() % 4 == 1 {
+        clean.pop();
+    }
+    BASE64_NOPAD.decode(&clean).unwrap_or_default()
+}
+
+/// =XX escapes and soft line breaks (= at the end of a line)
+pub fn decode_quoted_printable(bytes: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'=' if bytes[index + 1..].starts_with(b"\r\n") => index += 3,
+            b'=' if bytes[index + 1..].starts_with(b"\n") => index += 2,
+            b'=' => match hex_byte(&bytes[index + 1..]) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    index += 3;
+                },
+                None => {
+                    decoded.push(b'=');
+                    index += 1;
+                },
+            },
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            },
+        }
+    }
+    decoded
+}
+
+/// Text in the given charset, unknown charsets are read as UTF-8
+pub fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    let encoding = Encoding::for_label(charset.trim().as_bytes()).unwrap_or(UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// %XX escapes of a RFC 2231 value
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], hex_byte(&bytes[index + 1..])) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            },
+        }
+    }
+    decoded
+}
+
+/// Byte of the two hex digits at the start of bytes
+fn hex_byte(bytes: &[u8]) -> Option<u8> {
+    u8::from_str_radix(std::str::from_utf8(bytes.get(..2)?).ok()?, 16).ok()
+}
+
+/// Parts of a multipart body, between the --boundary lines (preamble and epilogue are dropped)
+pub fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = vec![];
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive(|b| *b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if let Some(rest) = trimmed.strip_prefix(delimiter.as_bytes()) {
+            if rest.is_empty() || rest == b"--" {
+                if let Some(start) = start {
+                    parts.push(&body[start..offset]);
+                }
+                if rest == b"--" {
+                    return parts;
+                }
+                start = Some(offset + line.len());
+            }
+        }
+        offset += line.len();
+    }
+    // Missing closing delimiter
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mime_decode() {
+        assert_eq!("Café déjà vu", decode_words("=?utf-8?Q?Caf=C3=A9_?= =?ISO-8859-1?B?ZOlq4A==?= vu"));
+        assert_eq!("Re: plain", decode_words("Re: plain"));
+        assert_eq!(b"line one continued\nx=y".to_vec(), decode_quoted_printable(b"line one =\ncontinued\nx=3Dy"));
+        assert_eq!(b"hello world".to_vec(), decode_base64(b"aGVsbG8g\r\nd29ybGQ="));
+        assert_eq!("Grüße", decode_charset(b"Gr\xfc\xdfe", "windows-1252"));
+
+        let (value, parameters) = parameters("attachment; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf; size=12");
+        assert_eq!("attachment", value);
+        assert_eq!(Some("résumé.pdf"), parameter(&parameters, "filename"));
+
+        let (headers, body) = split_message(b"Subject: a long\r\n  subject\r\nFrom: me\r\n\r\nbody\r\n");
+        assert_eq!(Some("a long subject"), header(&headers, "subject"));
+        assert_eq!(b"body\r\n", body);
+
+        let body = b"preamble\n--b1\nContent-Type: text/plain\n\none\n--b1\n\ntwo\n--b1--\nepilogue";
+        assert_eq!(vec![&b"Content-Type: text/plain\n\none\n"[..], &b"\ntwo\n"[..]], split_multipart(body, "b1"));
+    }
+}
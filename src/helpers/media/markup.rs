@@ -0,0 +1,527 @@
+use std::io;
+use once_cell::sync::Lazy;
+use quick_xml::escape::resolve_html5_entity;
+use regex::{Captures, Regex};
+use crate::helpers::file::{self, encoding};
+
+/// Elements ending a line of text, table cells are separated by a space
+const HTML_BLOCKS: [&str; 36] = [
+    "address", "article", "aside", "blockquote", "body", "br", "caption", "dd", "details", "div", "dl", "dt",
+    "fieldset", "figcaption", "figure", "footer", "form", "head", "header", "hr", "html", "li", "main", "nav",
+    "ol", "option", "p", "pre", "section", "summary", "table", "tbody", "tfoot", "thead", "tr", "ul",
+];
+
+/// Elements starting a new section, their text is the heading
+const HEADINGS: [&str; 8] = ["title", "h1", "h2", "h3", "h4", "h5", "h6", "heading"];
+
+/// Elements whose content is not text
+const SKIPPED: [&str; 2] = ["script", "style"];
+
+/// Admonitions of reStructuredText, the argument of the directive is text
+const RST_ADMONITIONS: [&str; 13] = [
+    "admonition", "attention", "caution", "danger", "error", "hint", "important", "note", "rubric", "sidebar", "tip", "topic", "warning",
+];
+
+static ENTITY: Lazy<Regex> = Lazy::new(|| Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z][a-zA-Z0-9]{1,31});").unwrap());
+
+static MD_HEADING: Lazy<Regex> = Lazy::new(|| Regex::new(r"^ {0,3}#{1,6}(?:\s+(.*?))?(?:\s+#+)?\s*$").unwrap());
+static MD_SETEXT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^ {0,3}(?:=+|-+)\s*$").unwrap());
+/// Thematic breaks, table separators and link reference definitions
+static MD_SKIPPED: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(?:(?:[-*_]\s*){3,}|\|?(?:\s*:?-+:?\s*\|)+\s*(?::?-+:?)?\s*|\[[^\]]+\]:\s+\S+.*)$").unwrap());
+/// Quote, list and task markers
+static MD_BLOCK: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(?:>\s?)*(?:(?:[-*+]|\d{1,9}[.)])\s+)?(?:\[[ xX]\]\s+)?").unwrap());
+static MD_INLINE: Lazy<[(Regex, &str); 11]> = Lazy::new(|| [
+    (Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap(), "$1"),
+    (Regex::new(r"\[([^\]]+)\](?:\([^)]*\)|\[[^\]]*\])").unwrap(), "$1"),
+    (Regex::new(r"<((?:https?|ftp|mailto):[^>\s]+)>").unwrap(), "$1"),
+    (Regex::new(r"</?[a-zA-Z][^>]*>").unwrap(), ""),
+    (Regex::new(r"`+").unwrap(), ""),
+    (Regex::new(r"\*\*(.+?)\*\*").unwrap(), "$1"),
+    (Regex::new(r"(^|\W)__(.+?)__(\W|$)").unwrap(), "$1$2$3"),
+    (Regex::new(r"\*(\S(?:[^*]*\S)?)\*").unwrap(), "$1"),
+    (Regex::new(r"(^|\W)_(\S(?:[^_]*\S)?)_(\W|$)").unwrap(), "$1$2$3"),
+    (Regex::new(r"~~(.+?)~~").unwrap(), "$1"),
+    (Regex::new(r"\\([!-/:-@\[-`{-~])").unwrap(), "$1"),
+]);
+
+static RST_DIRECTIVE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\.\.\s+([\w-]+)::\s*(.*)$").unwrap());
+static RST_TABLE_BORDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[+|]?[-= +|]*[-=]{2,}[-= +|]*$").unwrap());
+static RST_BLOCK: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:[-*+•]|\d{1,9}[.)]|#\.)\s+").unwrap());
+static RST_INLINE: Lazy<[(Regex, &str); 6]> = Lazy::new(|| [
+    (Regex::new(r":[\w:.+-]+:`([^`<]+?)(?:\s*<[^>]*>)?`").unwrap(), "$1"),
+    (Regex::new(r"`([^`<]+?)\s*<[^>]*>`_{1,2}").unwrap(), "$1"),
+    (Regex::new(r"``(.+?)``").unwrap(), "$1"),
+    (Regex::new(r"`([^`]+)`_{0,2}").unwrap(), "$1"),
+    (Regex::new(r"\*\*(.+?)\*\*").unwrap(), "$1"),
+    (Regex::new(r"\*(\S(?:[^*]*\S)?)\*").unwrap(), "$1"),
+]);
+
+/// Text of a markup file under its nearest heading
+#[derive(Debug, Default)]
+pub struct MarkupSection {
+    /// Text of the heading, empty before the first one
+    pub heading: String,
+    /// Line number in the file (from 1) and text of the lines, without the markup
+    pub lines: Vec<(usize, String)>,
+}
+
+/// Text of a html, xml, markdown or reStructuredText file, by heading
+pub fn read_markup(file_path: &str) -> Result<Vec<MarkupSection>, io::Error> {
+    let content = encoding::read_text(file_path)?;
+    Ok(markup_sections(&content, &file::get_extension(file_path).to_lowercase()))
+}
+
+/// Sections of the content, the extension gives the syntax: md, rst, xml, html otherwise
+pub fn markup_sections(content: &str, extension: &str) -> Vec<MarkupSection> {
+    match extension {
+        "md" | "markdown" => markdown_sections(content),
+        "rst" => rst_sections(content),
+        "xml" => TagReader::new(false).read(content),
+        _ => TagReader::new(true).read(content),
+    }
+}
+
+/// Text of a html page, one line by block, without the tags, the scripts and the styles
+pub fn html_to_text(html: &str) -> String {
+    TagReader::new(true)
+        .read(html)
+        .iter()
+        .flat_map(|section| section.lines.iter().map(|(_, text)| text.as_str()))
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Named and numeric character references replaced by their character, unknown names are kept
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    ENTITY.replace_all(text, |captures: &Captures| {
+        let name = &captures[1];
+        match name.strip_prefix('#') {
+            Some(number) => match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => number.parse::<u32>().ok(),
+            }.and_then(char::from_u32).map(String::from),
+            None => resolve_html5_entity(name).map(String::from),
+        }.unwrap_or_else(|| captures[0].to_string())
+    }).to_string()
+}
+
+/// Sections built line by line, with whitespace collapsed
+#[derive(Default)]
+struct Sections(Vec<MarkupSection>);
+
+impl Sections {
+    fn push_line(&mut self, line: usize, text: &str) {
+        let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if text.is_empty() {
+            return;
+        }
+        if self.0.is_empty() {
+            self.0.push(MarkupSection::default());
+        }
+        if let Some(section) = self.0.last_mut() {
+            section.lines.push((line, text));
+        }
+    }
+
+    /// Start a section, the heading is its first line
+    fn push_heading(&mut self, line: usize, heading: &str) {
+        let heading = heading.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if heading.is_empty() {
+            return;
+        }
+        self.0.push(MarkupSection {
+            heading: heading.clone(),
+            lines: vec![(line, heading)],
+        });
+    }
+}
+
+/// Text of html and xml content
+///
+/// html: blocks (p, li, tr...) end the lines, whitespace is collapsed except in pre,
+/// xml: tags separate the words, lines are kept as in the file
+struct TagReader {
+    html: bool,
+    sections: Sections,
+    /// Text of the current line, entities not decoded yet
+    text: String,
+    /// Line of the first character of text
+    text_line: usize,
+    line: usize,
+    in_heading: bool,
+    in_pre: usize,
+}
+
+/// Start or end tag, declarations and processing instructions have no name
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    length: usize,
+}
+
+impl TagReader {
+    fn new(html: bool) -> Self {
+        TagReader {
+            html,
+            sections: Sections::default(),
+            text: String::new(),
+            text_line: 1,
+            line: 1,
+            in_heading: false,
+            in_pre: 0,
+        }
+    }
+
+    fn read(mut self, content: &str) -> Vec<MarkupSection> {
+        let mut rest = content;
+        while let Some(character) = rest.chars().next() {
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let end = comment.find("-->").map_or(comment.len(), |end| end + 3);
+                self.line += comment[..end].matches('\n').count();
+                rest = &comment[end..];
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").unwrap_or(cdata.len());
+                // Entities are decoded with the line, the content of CDATA is kept as written
+                for character in cdata[..end].replace('&', "&amp;").chars() {
+                    self.push_char(character);
+                }
+                rest = cdata.get(end + 3..).unwrap_or_default();
+            } else if let Some(tag) = (character == '<').then(|| parse_tag(rest)).flatten() {
+                self.line += rest[..tag.length].matches('\n').count();
+                rest = &rest[tag.length..];
+                if self.html && SKIPPED.contains(&tag.name.as_str()) && !tag.closing && !tag.self_closing {
+                    self.flush();
+                    let end = find_end_tag(rest, &tag.name);
+                    self.line += rest[..end].matches('\n').count();
+                    rest = &rest[end..];
+                } else {
+                    self.push_tag(&tag);
+                }
+            } else {
+                self.push_char(character);
+                rest = &rest[character.len_utf8()..];
+            }
+        }
+        self.flush();
+        self.sections.0
+    }
+
+    fn push_char(&mut self, character: char) {
+        if character == '\n' {
+            if !self.html || self.in_pre > 0 {
+                self.flush();
+            } else {
+                self.text.push(' ');
+            }
+            self.line += 1;
+            return;
+        }
+        if self.text.trim().is_empty() && !character.is_whitespace() {
+            self.text_line = self.line;
+        }
+        self.text.push(character);
+    }
+
+    fn push_tag(&mut self, tag: &Tag) {
+        let name = tag.name.as_str();
+        if HEADINGS.contains(&name) {
+            if tag.closing && self.in_heading {
+                let heading = decode_entities(&self.text);
+                self.sections.push_heading(self.text_line, &heading);
+                self.text.clear();
+                self.in_heading = false;
+            } else if !tag.closing && !tag.self_closing {
+                self.flush();
+                self.in_heading = true;
+            }
+        } else if self.html && name == "pre" {
+            self.flush();
+            self.in_pre = if tag.closing { self.in_pre.saturating_sub(1) } else { self.in_pre + 1 };
+        } else if self.html && HTML_BLOCKS.contains(&name) {
+            self.flush();
+        } else if !self.html || matches!(name, "td" | "th") {
+            self.text.push(' ');
+        }
+    }
+
+    /// End the current line
+    fn flush(&mut self) {
+        if !self.text.trim().is_empty() {
+            self.sections.push_line(self.text_line, &decode_entities(&self.text));
+        }
+        self.text.clear();
+        self.in_heading = false;
+    }
+}
+
+/// Tag at the start of content, attribute values may contain '>'
+fn parse_tag(content: &str) -> Option<Tag> {
+    let bytes = content.as_bytes();
+    let (closing, name_start) = if *bytes.get(1)? == b'/' { (true, 2) } else { (false, 1) };
+    let name: String = content[name_start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '-' | '.' | '!' | '?'))
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '!' || c == '?') {
+        return None;
+    }
+    let mut quote: Option<u8> = None;
+    for (index, byte) in bytes.iter().enumerate().skip(name_start + name.len()) {
+        match (quote, byte) {
+            (Some(q), b) if q == *b => quote = None,
+            (Some(_), _) => (),
+            (None, b'"' | b'\'') => quote = Some(*byte),
+            (None, b'>') => return Some(Tag {
+                // Local name, without the namespace prefix
+                name: name.rsplit(':').next().unwrap_or_default().to_lowercase(),
+                closing,
+                self_closing: bytes[index - 1] == b'/',
+                length: index + 1,
+            }),
+            (None, b'<') => return None,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Position after the end tag of the element, or the end of content
+fn find_end_tag(content: &str, name: &str) -> usize {
+    content
+        .match_indices("</")
+        .map(|(start, _)| start)
+        .find(|start| content.as_bytes()[start + 2..].get(..name.len()).is_some_and(|tag| tag.eq_ignore_ascii_case(name.as_bytes())))
+        .map(|start| start + content[start..].find('>').map_or(content.len() - start, |end| end + 1))
+        .unwrap_or(content.len())
+}
+
+/// Text of a markdown file: headings (# and underlined), code blocks kept,
+/// links, emphasis, html tags and list markers removed
+fn markdown_sections(content: &str) -> Vec<MarkupSection> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut sections = Sections::default();
+    let mut index = 0;
+    // Front matter
+    if lines.first().map(|line| line.trim_end()) == Some("---") {
+        if let Some(end) = lines.iter().skip(1).position(|line| matches!(line.trim_end(), "---" | "...")) {
+            for (number, line) in lines.iter().enumerate().take(end + 1).skip(1) {
+                sections.push_line(number + 1, line);
+            }
+            index = end + 2;
+        }
+    }
+    let mut fence: Option<&str> = None;
+    while index < lines.len() {
+        let (number, line) = (index + 1, lines[index]);
+        let trimmed = line.trim();
+        index += 1;
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            } else {
+                sections.push_line(number, line);
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        } else if let Some(captures) = MD_HEADING.captures(line) {
+            sections.push_heading(number, &markdown_inline(captures.get(1).map_or("", |m| m.as_str())));
+        } else if !trimmed.is_empty() && !line.starts_with("    ") && lines.get(index).is_some_and(|next| MD_SETEXT.is_match(next)) {
+            sections.push_heading(number, &markdown_inline(&MD_BLOCK.replace(line, "")));
+            index += 1;
+        } else if !MD_SKIPPED.is_match(line) {
+            let text = MD_BLOCK.replace(line, "");
+            let text = if text.trim_start().starts_with('|') { text.replace('|', " ") } else { text.to_string() };
+            sections.push_line(number, &markdown_inline(&text));
+        }
+    }
+    sections.0
+}
+
+fn markdown_inline(text: &str) -> String {
+    let mut text = text.to_string();
+    for (regex, replacement) in MD_INLINE.iter() {
+        text = regex.replace_all(&text, *replacement).to_string();
+    }
+    decode_entities(&text)
+}
+
+/// Text of a reStructuredText file: underlined (and overlined) titles are the headings,
+/// directives other than admonitions, comments and table borders are removed
+fn rst_sections(content: &str) -> Vec<MarkupSection> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut sections = Sections::default();
+    let mut index = 0;
+    while index < lines.len() {
+        let (number, line) = (index + 1, lines[index]);
+        let trimmed = line.trim();
+        let next = lines.get(index + 1).copied().unwrap_or_default();
+        if let Some(adornment) = rst_adornment(line) {
+            // Overlined title
+            let title = next.trim();
+            if !title.is_empty() && lines.get(index + 2).and_then(|line| rst_adornment(line)) == Some(adornment) {
+                sections.push_heading(number + 1, &rst_inline(title));
+                index += 3;
+            } else {
+                index += 1;
+            }
+            continue;
+        }
+        index += 1;
+        if !trimmed.is_empty() && !line.starts_with(char::is_whitespace)
+            && rst_adornment(next).is_some() && next.trim_end().chars().count() >= trimmed.chars().count() {
+            sections.push_heading(number, &rst_inline(trimmed));
+            index += 1;
+        } else if trimmed.starts_with("..") {
+            if let Some(captures) = RST_DIRECTIVE.captures(trimmed).filter(|captures| RST_ADMONITIONS.contains(&&captures[1])) {
+                sections.push_line(number, &rst_inline(&captures[2]));
+            }
+        } else if !RST_TABLE_BORDER.is_match(trimmed) {
+            let text = RST_BLOCK.replace(trimmed, "");
+            let text = if text.starts_with('|') && text.ends_with('|') { text.replace('|', " ") } else { text.to_string() };
+            // Literal block marker: "text::" is displayed "text:", a "::" line is removed
+            let text = match text.strip_suffix("::") {
+                Some(text) if text.trim().is_empty() => String::new(),
+                Some(text) => format!("{}:", text.trim_end()),
+                None => text,
+            };
+            sections.push_line(number, &rst_inline(&text));
+        }
+    }
+    sections.0
+}
+
+/// Character of a title adornment line: ===== or -----
+fn rst_adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+    let is_adornment = "=-~^\"'`#*+:._".contains(first) && line.chars().count() >= 2 && line.chars().all(|c| c == first);
+    is_adornment.then_some(first)
+}
+
+fn rst_inline(text: &str) -> String {
+    let mut text = text.to_string();
+    for (regex, replacement) in RST_INLINE.iter() {
+        text = regex.replace_all(&text, *replacement).to_string();
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines(sections: &[MarkupSection]) -> Vec<(&str, usize, &str)> {
+        sections
+            .iter()
+            .flat_map(|section| section.lines.iter().map(|(line, text)| (section.heading.as_str(), *line, text.as_str())))
+            .collect()
+    }
+
+    #[test]
+    fn markup_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Wiki &amp; notes</title>
+<style>p { color: red }</style>
+<script>if (a < b) { alert("budget") }</script></head>
+<body>
+<h1 class="title">Install<!-- draft --></h1>
+<p>Run <code>make</code> then
+  <a href="/budget?a=1&amp;b=2" title="x > y">read&nbsp;the guide</a>.</p>
+<table><tr><td>caf&eacute;</td><td>&#x20AC;5</td></tr></table>
+<h2>Usage</h2><ul><li>one</li><li>two &lt;b&gt;</li></ul>
+</body></html>"#;
+        assert_eq!(vec![
+            ("Wiki & notes", 2, "Wiki & notes"),
+            ("Install", 6, "Install"),
+            ("Install", 7, "Run make then read the guide."),
+            ("Install", 9, "café €5"),
+            ("Usage", 10, "Usage"),
+            ("Usage", 10, "one"),
+            ("Usage", 10, "two <b>"),
+        ], lines(&markup_sections(html, "html")));
+        assert_eq!("OK & thanks", html_to_text("<html><head><style>p {}</style></head><body><p>OK &amp; thanks</p></body></html>"));
+        assert_eq!("España", html_to_text("<p>Espa&ntilde;a</p><SCRIPT>var s = \"</scr\";</Script >"));
+        assert_eq!("&unknown; ½", decode_entities("&unknown; &frac12;"));
+
+        let xml = "<?xml version=\"1.0\"?>\n<book>\n  <title>Dune</title>\n  <para>Arrakis <emphasis>desert</emphasis>\n  planet</para>\n  <note><![CDATA[a < b & c]]></note>\n</book>";
+        assert_eq!(vec![
+            ("Dune", 3, "Dune"),
+            ("Dune", 4, "Arrakis desert"),
+            ("Dune", 5, "planet"),
+            ("Dune", 6, "a < b & c"),
+        ], lines(&markup_sections(xml, "xml")));
+    }
+
+    #[test]
+    fn markup_markdown() {
+        let markdown = "---
+title: Notes
+---
+Intro with **bold** and `code`
+
+# Install &amp; run #
+
+1. Get the [source](https://example.com/src) and ![logo](logo.png)
+- [x] done_item with _emphasis_
+
+| Name | Value |
+|------|:-----:|
+| size | 42    |
+
+Usage
+-----
+```sh
+make <install>
+```
+[ref]: https://example.com
+> quoted <span>text</span>";
+        assert_eq!(vec![
+            ("", 2, "title: Notes"),
+            ("", 4, "Intro with bold and code"),
+            ("Install & run", 6, "Install & run"),
+            ("Install & run", 8, "Get the source and logo"),
+            ("Install & run", 9, "done_item with emphasis"),
+            ("Install & run", 11, "Name Value"),
+            ("Install & run", 13, "size 42"),
+            ("Usage", 15, "Usage"),
+            ("Usage", 18, "make <install>"),
+            ("Usage", 21, "quoted text"),
+        ], lines(&markup_sections(markdown, "md")));
+    }
+
+    #[test]
+    fn markup_rst() {
+        let rst = "=====
+Guide
+=====
+
+Read the :ref:`setup <setup-label>` page and `the site <https://example.com>`_.
+
+Setup
+-----
+
+.. note:: Back up **first**
+.. image:: screen.png
+
+* Run ``make``::
+
+    make install
+
+=====  =====
+Name   Value
+=====  =====";
+        assert_eq!(vec![
+            ("Guide", 2, "Guide"),
+            ("Guide", 5, "Read the setup page and the site."),
+            ("Setup", 7, "Setup"),
+            ("Setup", 10, "Back up first"),
+            ("Setup", 13, "Run make:"),
+            ("Setup", 15, "make install"),
+            ("Setup", 18, "Name Value"),
+        ], lines(&markup_sections(rst, "rst")));
+    }
+}
@@ -0,0 +1,127 @@
+pub mod odf;
+pub mod cfb;
+pub mod legacy;
+
+use core::fmt;
+use std::io::{self, Read};
+
+use colored::Colorize;
+use dotext::{Docx, MsDoc, Pptx, Xlsx};
+use serde::{Deserialize, Serialize};
+use sha256::digest;
+use crate::helpers::file;
+
+use super::normalize_media_title;
+
+/// Max length of the summary when the document has no description
+const SUMMARY_LENGTH: usize = 500;
+
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OfficeResult {
+    pub title: String,
+    pub summary: String,
+    pub content: String,
+
+    pub genres: Vec<String>,
+    pub casts: Vec<String>,
+
+    pub provider: String,
+
+    pub rating: f32,
+    pub file_type: String,
+    pub file_path: String,
+    pub full_path: String,
+    pub hash: String,
+    pub modification_time: u64,
+    pub duration: usize,
+    pub file_size: usize,
+}
+
+impl fmt::Display for OfficeResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut str = String::new();
+        str.push_str(&format!("Title: {}\n", self.title.bold()));
+
+        str.push_str(&format!("\n{}\n", self.summary));
+        str.push_str(&format!("\nAuthors: {}\n", self.casts.join(", ")));
+        str.push_str(&format!("\nKeywords: {}\n", self.genres.join(", ")));
+
+        write!(f, "{str}")
+    }
+}
+
+/// Text content of an office file: docx, xlsx, pptx, odt, ods, odp and the 97-2003 formats doc, xls, ppt
+///
+/// `extension` is the type of the file detected by magic::media_extension
+pub fn get_office_content(file_path: &str, extension: &str) -> Result<String, io::Error> {
+    let mut content = String::new();
+    match extension {
+        "docx" => {
+            Docx::open(file_path)?.read_to_string(&mut content)?;
+        },
+        "xlsx" => {
+            Xlsx::open(file_path)?.read_to_string(&mut content)?;
+        },
+        "pptx" => {
+            Pptx::open(file_path)?.read_to_string(&mut content)?;
+        },
+        "odt" | "ods" | "odp" => {
+            content = odf::read_content(file_path)?;
+        },
+        "doc" => {
+            content = legacy::read_doc(file_path)?;
+        },
+        "xls" => {
+            content = legacy::read_xls(file_path)?;
+        },
+        "ppt" => {
+            content = legacy::read_ppt(file_path)?;
+        },
+        extension => return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Unsupported office format {extension}")
+        )),
+    }
+    Ok(content)
+}
+
+pub fn get_office_result(base_path: &String, file_path: &String) -> Result<OfficeResult, io::Error> {
+    let file_size: usize = file::file_size(file_path).unwrap_or_default() as usize;
+    let file_name = file::get_file_name(file_path);
+    let relative_file_path = file_path.replace(base_path, "");
+
+    let hash = file::sha256(file_path).unwrap_or(digest(&relative_file_path));
+
+    let content = get_office_content(file_path, &file::magic::media_extension(file_path))?;
+    // Only OpenDocument metadata are read (meta.xml)
+    let metadata = odf::read_metadata(file_path).unwrap_or_default();
+
+    let title = if metadata.title.is_empty() { file_name } else { metadata.title };
+    let summary = if !metadata.description.is_empty() {
+        metadata.description
+    } else if !metadata.subject.is_empty() {
+        metadata.subject
+    } else {
+        content.trim().chars().take(SUMMARY_LENGTH).collect()
+    };
+
+    Ok(OfficeResult {
+        title: normalize_media_title(&title),
+        summary,
+        content,
+        genres: metadata.keywords,
+        casts: if metadata.creator.is_empty() { vec![] } else { vec![metadata.creator] },
+
+        provider: String::from("local"),
+
+        rating: 1.,
+        file_type: String::from("office"),
+        file_path: relative_file_path,
+        full_path: file_path.to_string(),
+        hash,
+        modification_time: file::get_creation_time(file_path),
+        duration: 0,
+        file_size,
+    })
+}
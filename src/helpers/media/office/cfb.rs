@@ -0,0 +1,324 @@
+use std::{collections::HashSet, fs::File, io::{self, BufReader, Cursor, Read, Seek, SeekFrom}};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Signature of the compound files (OLE2): doc, xls, ppt, msg...
+pub const CFB_SIGNATURE: [u8; 8] = [0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1];
+
+/// Sector ids above this value are markers (end of chain, free, fat, difat)
+const MAX_SECTOR: u32 = 0xfffffffa;
+const HEADER_DIFAT_COUNT: usize = 109;
+const DIRECTORY_ENTRY_SIZE: usize = 128;
+const MINI_SECTOR_SIZE: usize = 64;
+
+/// Type of a directory entry
+const STREAM_ENTRY: u8 = 2;
+const ROOT_ENTRY: u8 = 5;
+
+/// A directory entry: name, first sector and size
+struct Entry {
+    name: String,
+    kind: u8,
+    start: u32,
+    size: usize,
+}
+
+/// Compound File Binary format (MS-CFB): a file system of streams inside a file,
+/// used by the Office 97-2003 formats
+///
+/// Only reading streams by name is supported, storages are not walked
+pub struct CompoundFile {
+    bytes: Vec<u8>,
+    sector_size: usize,
+    mini_cutoff: usize,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl CompoundFile {
+    pub fn new(bytes: Vec<u8>) -> Result<Self> {
+        let directory = Directory::read(&mut Cursor::new(&bytes))?;
+        let mut cfb = CompoundFile {
+            sector_size: directory.sector_size,
+            mini_cutoff: directory.mini_cutoff,
+            fat: directory.fat,
+            mini_fat: vec![],
+            mini_stream: vec![],
+            entries: directory.entries,
+            bytes,
+        };
+
+        let mini_fat = cfb.read_chain(u32_at(&cfb.bytes, 0x3c), None);
+        cfb.mini_fat = (0..mini_fat.len() / 4).map(|i| u32_at(&mini_fat, i * 4)).collect();
+        if let Some(root) = cfb.entries.iter().find(|entry| entry.kind == ROOT_ENTRY) {
+            cfb.mini_stream = cfb.read_chain(root.start, Some(root.size));
+        }
+        Ok(cfb)
+    }
+
+    /// Content of the first stream with this name, in any storage
+    pub fn stream(&self, name: &str) -> Result<Vec<u8>> {
+        let Some(entry) = self.entries.iter().find(|entry| entry.kind == STREAM_ENTRY && entry.name == name) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("stream {name} not found")));
+        };
+        if entry.size < self.mini_cutoff {
+            return Ok(self.read_mini_chain(entry.start, entry.size));
+        }
+        Ok(self.read_chain(entry.start, Some(entry.size)))
+    }
+
+    pub fn has_stream(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.kind == STREAM_ENTRY && entry.name == name)
+    }
+
+    fn sector(&self, sector: u32) -> Option<&[u8]> {
+        let offset = (sector as usize + 1).checked_mul(self.sector_size)?;
+        self.bytes.get(offset..offset + self.sector_size)
+    }
+
+    /// Sectors of a chain in the FAT, the length of the chain is bounded by the FAT to stop on loops
+    fn read_chain(&self, start: u32, size: Option<usize>) -> Vec<u8> {
+        let mut content = vec![];
+        let mut sector = start;
+        for _ in 0..self.fat.len() {
+            let Some(data) = (sector <= MAX_SECTOR).then(|| self.sector(sector)).flatten() else { break };
+            content.extend_from_slice(data);
+            sector = self.fat.get(sector as usize).copied().unwrap_or(u32::MAX);
+        }
+        if let Some(size) = size {
+            content.truncate(size);
+        }
+        content
+    }
+
+    /// Small streams are stored in 64 bytes sectors of the mini stream
+    fn read_mini_chain(&self, start: u32, size: usize) -> Vec<u8> {
+        let mut content = vec![];
+        let mut sector = start;
+        for _ in 0..self.mini_fat.len() {
+            let offset = sector as usize * MINI_SECTOR_SIZE;
+            let Some(data) = (sector <= MAX_SECTOR).then(|| self.mini_stream.get(offset..offset + MINI_SECTOR_SIZE)).flatten() else { break };
+            content.extend_from_slice(data);
+            sector = self.mini_fat.get(sector as usize).copied().unwrap_or(u32::MAX);
+        }
+        content.truncate(size);
+        content
+    }
+}
+
+/// Names of the streams of a compound file, only the header, the FAT and the directory are read
+pub fn stream_names(file_path: &str) -> Result<Vec<String>> {
+    let directory = Directory::read(&mut BufReader::new(File::open(file_path)?))?;
+    Ok(directory.entries.into_iter().filter(|entry| entry.kind == STREAM_ENTRY).map(|entry| entry.name).collect())
+}
+
+/// Header values, FAT and directory entries, the sectors are read from the reader when needed
+struct Directory {
+    sector_size: usize,
+    mini_cutoff: usize,
+    fat: Vec<u32>,
+    entries: Vec<Entry>,
+}
+
+impl Directory {
+    fn read<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let mut header = vec![0; 512];
+        if reader.read_exact(&mut header).is_err() || header[..8] != CFB_SIGNATURE {
+            return Err(cfb_error("not a compound file"));
+        }
+        let sector_shift = u16_at(&header, 0x1e) as u32;
+        if !(7..=16).contains(&sector_shift) {
+            return Err(cfb_error("invalid sector size"));
+        }
+        let mut directory = Directory {
+            sector_size: 1 << sector_shift,
+            mini_cutoff: u32_at(&header, 0x38) as usize,
+            fat: vec![],
+            entries: vec![],
+        };
+
+        // Sectors of the FAT: 109 in the header, the others in the DIFAT sectors chain,
+        // bounded by the sectors of the file and stopped on loops (corrupt or crafted files)
+        let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT_COUNT).map(|i| u32_at(&header, 0x4c + i * 4)).collect();
+        let mut difat_sector = u32_at(&header, 0x44);
+        let per_sector = directory.sector_size / 4;
+        let sector_count = reader.seek(SeekFrom::End(0))? / directory.sector_size as u64;
+        let mut visited = HashSet::new();
+        for _ in 0..(u32_at(&header, 0x48) as u64).min(sector_count) {
+            if difat_sector > MAX_SECTOR || !visited.insert(difat_sector) {
+                break;
+            }
+            let Some(sector) = directory.sector(reader, difat_sector) else { break };
+            fat_sectors.extend((0..per_sector - 1).map(|i| u32_at(&sector, i * 4)));
+            difat_sector = u32_at(&sector, (per_sector - 1) * 4);
+        }
+        for fat_sector in fat_sectors.into_iter().filter(|s| *s <= MAX_SECTOR) {
+            let Some(sector) = directory.sector(reader, fat_sector) else { break };
+            let entries: Vec<u32> = (0..per_sector).map(|i| u32_at(&sector, i * 4)).collect();
+            directory.fat.extend(entries);
+        }
+
+        // Directory chain, bounded by the FAT to stop on loops
+        let mut content = vec![];
+        let mut sector = u32_at(&header, 0x30);
+        for _ in 0..directory.fat.len() {
+            let Some(data) = (sector <= MAX_SECTOR).then(|| directory.sector(reader, sector)).flatten() else { break };
+            content.extend_from_slice(&data);
+            sector = directory.fat.get(sector as usize).copied().unwrap_or(u32::MAX);
+        }
+        directory.entries = content.chunks_exact(DIRECTORY_ENTRY_SIZE).map(|entry| {
+            let name_length = (u16_at(entry, 64) as usize).min(64);
+            let name: Vec<u16> = (0..name_length / 2).map(|i| u16_at(entry, i * 2)).take_while(|c| *c != 0).collect();
+            Entry {
+                name: String::from_utf16_lossy(&name),
+                kind: entry[66],
+                start: u32_at(entry, 116),
+                size: u32_at(entry, 120) as usize,
+            }
+        }).collect();
+        Ok(directory)
+    }
+
+    fn sector<R: Read + Seek>(&self, reader: &mut R, sector: u32) -> Option<Vec<u8>> {
+        let offset = (sector as u64 + 1).checked_mul(self.sector_size as u64)?;
+        reader.seek(SeekFrom::Start(offset)).ok()?;
+        let mut data = vec![0; self.sector_size];
+        reader.read_exact(&mut data).ok()?;
+        Some(data)
+    }
+}
+
+pub fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    match bytes.get(offset..offset + 2) {
+        Some(b) => u16::from_le_bytes([b[0], b[1]]),
+        None => 0,
+    }
+}
+
+pub fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    match bytes.get(offset..offset + 4) {
+        Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        None => 0,
+    }
+}
+
+fn cfb_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    const END_OF_CHAIN: u32 = 0xfffffffe;
+    const FAT_SECTOR: u32 = 0xfffffffd;
+    const FREE_SECTOR: u32 = 0xffffffff;
+
+    /// Sectors of data, returns the first one
+    fn push_chain(sectors: &mut Vec<Vec<u8>>, fat: &mut Vec<u32>, data: &[u8]) -> u32 {
+        let start = sectors.len() as u32;
+        let chunks: Vec<&[u8]> = data.chunks(512).collect();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let mut sector = chunk.to_vec();
+            sector.resize(512, 0);
+            sectors.push(sector);
+            fat.push(if index + 1 == chunks.len() { END_OF_CHAIN } else { start + index as u32 + 1 });
+        }
+        start
+    }
+
+    /// A compound file of 512 bytes sectors with the given streams,
+    /// streams shorter than 4096 bytes go in the mini stream
+    pub fn compound_file(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut sectors: Vec<Vec<u8>> = vec![];
+        let mut fat: Vec<u32> = vec![];
+        // Sector 0 is the FAT
+        sectors.push(vec![]);
+        fat.push(FAT_SECTOR);
+
+        let mut mini_stream = vec![];
+        let mut mini_fat: Vec<u32> = vec![];
+        let mut entries = vec![];
+        for (name, data) in streams {
+            let start = if data.len() < 4096 {
+                let start = mini_fat.len() as u32;
+                let count = data.len().div_ceil(64);
+                for index in 0..count {
+                    mini_fat.push(if index + 1 == count { END_OF_CHAIN } else { start + index as u32 + 1 });
+                }
+                mini_stream.extend_from_slice(data);
+                mini_stream.resize(mini_fat.len() * 64, 0);
+                start
+            } else {
+                push_chain(&mut sectors, &mut fat, data)
+            };
+            entries.push((name.to_string(), STREAM_ENTRY, start, data.len()));
+        }
+        let mini_stream_start = push_chain(&mut sectors, &mut fat, &mini_stream);
+        let mini_fat_bytes: Vec<u8> = mini_fat.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mini_fat_start = push_chain(&mut sectors, &mut fat, &mini_fat_bytes);
+        entries.insert(0, (String::from("Root Entry"), ROOT_ENTRY, mini_stream_start, mini_stream.len()));
+        let mut directory = vec![];
+        for (name, kind, start, size) in entries {
+            let mut entry = vec![0; DIRECTORY_ENTRY_SIZE];
+            let name: Vec<u8> = name.encode_utf16().chain([0]).flat_map(|c| c.to_le_bytes()).collect();
+            entry[..name.len()].copy_from_slice(&name);
+            entry[64..66].copy_from_slice(&(name.len() as u16).to_le_bytes());
+            entry[66] = kind;
+            entry[116..120].copy_from_slice(&start.to_le_bytes());
+            entry[120..124].copy_from_slice(&(size as u32).to_le_bytes());
+            directory.extend(entry);
+        }
+        let directory_start = push_chain(&mut sectors, &mut fat, &directory);
+        fat.resize(128, FREE_SECTOR);
+        sectors[0] = fat.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+        let mut header = vec![0; 512];
+        header[..8].copy_from_slice(&CFB_SIGNATURE);
+        header[0x1e..0x20].copy_from_slice(&9u16.to_le_bytes());
+        header[0x20..0x22].copy_from_slice(&6u16.to_le_bytes());
+        header[0x2c..0x30].copy_from_slice(&1u32.to_le_bytes());
+        header[0x30..0x34].copy_from_slice(&directory_start.to_le_bytes());
+        header[0x38..0x3c].copy_from_slice(&4096u32.to_le_bytes());
+        header[0x3c..0x40].copy_from_slice(&mini_fat_start.to_le_bytes());
+        header[0x44..0x48].copy_from_slice(&END_OF_CHAIN.to_le_bytes());
+        for index in 0..HEADER_DIFAT_COUNT {
+            let sector = if index == 0 { 0 } else { FREE_SECTOR };
+            header[0x4c + index * 4..0x50 + index * 4].copy_from_slice(&sector.to_le_bytes());
+        }
+        header.extend(sectors.concat());
+        header
+    }
+
+    #[test]
+    fn cfb_difat_loop() {
+        let mut bytes = compound_file(&[("Small", b"hello".to_vec())]);
+        // A DIFAT sector pointing to itself, with the max number of DIFAT sectors
+        let difat_sector = (bytes.len() / 512 - 1) as u32;
+        let mut sector = vec![0xff; 512];
+        sector[508..].copy_from_slice(&difat_sector.to_le_bytes());
+        bytes.extend(sector);
+        bytes[0x44..0x48].copy_from_slice(&difat_sector.to_le_bytes());
+        bytes[0x48..0x4c].copy_from_slice(&u32::MAX.to_le_bytes());
+        let cfb = CompoundFile::new(bytes).unwrap();
+        assert_eq!(b"hello".to_vec(), cfb.stream("Small").unwrap());
+    }
+
+    #[test]
+    fn cfb_streams() {
+        let large: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        let bytes = compound_file(&[("Small", b"hello".to_vec()), ("Large", large.clone())]);
+        let cfb = CompoundFile::new(bytes).unwrap();
+        assert_eq!(b"hello".to_vec(), cfb.stream("Small").unwrap());
+        assert_eq!(large, cfb.stream("Large").unwrap());
+        assert!(cfb.stream("Missing").is_err());
+        assert!(CompoundFile::new(vec![0; 1024]).is_err());
+
+        let path = std::env::temp_dir().join(format!("oms_cfb_{}.bin", std::process::id()));
+        std::fs::write(&path, compound_file(&[("WordDocument", large), ("Small", b"hello".to_vec())])).unwrap();
+        assert_eq!(vec!["WordDocument", "Small"], stream_names(&path.display().to_string()).unwrap());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,363 @@
+use std::{fs, io};
+use crate::helpers::file::encoding::decode_windows_1252;
+use super::cfb::{u16_at, u32_at, CompoundFile};
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Word 97 FIB: identifier, version, flags and location of the piece table (Clx)
+const WORD_IDENT: u16 = 0xa5ec;
+const WORD_97_VERSION: u16 = 0x00c1;
+const FIB_ENCRYPTED: u16 = 0x0100;
+const FIB_TABLE_1: u16 = 0x0200;
+const FIB_FC_CLX: usize = 0x01a2;
+/// Pieces stored as 8 bits chars
+const PIECE_COMPRESSED: u32 = 0x4000_0000;
+
+/// BIFF records of the cell strings
+const XLS_BOF: u16 = 0x0809;
+const XLS_SST: u16 = 0x00fc;
+const XLS_CONTINUE: u16 = 0x003c;
+const XLS_LABEL_SST: u16 = 0x00fd;
+const XLS_LABEL: u16 = 0x0204;
+const XLS_FILEPASS: u16 = 0x002f;
+const BIFF8_VERSION: u16 = 0x0600;
+
+/// PowerPoint records: containers have this version, text atoms in UTF-16 or 8 bits
+const PPT_CONTAINER: u16 = 0x000f;
+const PPT_TEXT_CHARS: u16 = 0x0fa0;
+const PPT_TEXT_BYTES: u16 = 0x0fa8;
+
+/// Text of a Word 97-2003 document, from its piece table
+pub fn read_doc(file_path: &str) -> Result<String> {
+    doc_text(&CompoundFile::new(fs::read(file_path)?)?)
+}
+
+/// Strings of the cells of an Excel 97-2003 workbook, one line per row
+pub fn read_xls(file_path: &str) -> Result<String> {
+    let cfb = CompoundFile::new(fs::read(file_path)?)?;
+    // BIFF5 workbooks (Excel 95) are in the Book stream
+    let stream = if cfb.has_stream("Workbook") { cfb.stream("Workbook")? } else { cfb.stream("Book")? };
+    xls_text(&stream)
+}
+
+/// Text of the slides and notes of a PowerPoint 97-2003 presentation
+pub fn read_ppt(file_path: &str) -> Result<String> {
+    let cfb = CompoundFile::new(fs::read(file_path)?)?;
+    Ok(ppt_text(&cfb.stream("PowerPoint Document")?))
+}
+
+fn doc_text(cfb: &CompoundFile) -> Result<String> {
+    let word = cfb.stream("WordDocument")?;
+    if u16_at(&word, 0) != WORD_IDENT || u16_at(&word, 2) < WORD_97_VERSION {
+        return Err(legacy_error("unsupported Word version, Word 97 or later required"));
+    }
+    let flags = u16_at(&word, 0x0a);
+    if flags & FIB_ENCRYPTED != 0 {
+        return Err(legacy_error("encrypted document"));
+    }
+    let table = cfb.stream(if flags & FIB_TABLE_1 != 0 { "1Table" } else { "0Table" })?;
+
+    // Clx: property modifiers (0x01) then the piece table (0x02)
+    let mut offset = u32_at(&word, FIB_FC_CLX) as usize;
+    while table.get(offset) == Some(&0x01) {
+        offset += 3 + u16_at(&table, offset + 1) as usize;
+    }
+    if table.get(offset) != Some(&0x02) {
+        return Err(legacy_error("piece table not found"));
+    }
+    let length = u32_at(&table, offset + 1) as usize;
+    let pieces = table.get(offset + 5..offset + 5 + length).ok_or_else(|| legacy_error("invalid piece table"))?;
+
+    // n + 1 character positions followed by n piece descriptors of 8 bytes
+    let count = length.saturating_sub(4) / 12;
+    let mut text = String::new();
+    for index in 0..count {
+        let chars = u32_at(pieces, (index + 1) * 4).saturating_sub(u32_at(pieces, index * 4)) as usize;
+        let fc = u32_at(pieces, (count + 1) * 4 + index * 8 + 2);
+        if fc & PIECE_COMPRESSED != 0 {
+            let start = ((fc & !PIECE_COMPRESSED) / 2) as usize;
+            if let Some(bytes) = word.get(start..start + chars) {
+                text.push_str(&decode_windows_1252(bytes));
+            }
+        } else if let Some(bytes) = word.get(fc as usize..fc as usize + chars * 2) {
+            let units: Vec<u16> = (0..chars).map(|i| u16_at(bytes, i * 2)).collect();
+            text.push_str(&String::from_utf16_lossy(&units));
+        }
+    }
+    Ok(clean_word_text(&text))
+}
+
+/// Word special chars: paragraphs and cells ends, field codes (only their result is kept)
+fn clean_word_text(text: &str) -> String {
+    let mut clean = String::with_capacity(text.len());
+    // A field is in its code until the separator, then in its result
+    let mut fields: Vec<bool> = vec![];
+    for c in text.chars() {
+        match c {
+            '\x13' => fields.push(true),
+            '\x14' => if let Some(code) = fields.last_mut() { *code = false },
+            '\x15' => { fields.pop(); },
+            _ if fields.iter().any(|code| *code) => (),
+            '\r' | '\x0b' | '\x0c' => clean.push('\n'),
+            '\x07' => clean.push('\t'),
+            '\x1e' => clean.push('-'),
+            '\t' | '\n' => clean.push(c),
+            _ if c.is_control() => (),
+            _ => clean.push(c),
+        }
+    }
+    clean
+}
+
+fn xls_text(stream: &[u8]) -> Result<String> {
+    let mut records = vec![];
+    let mut offset = 0;
+    while offset + 4 <= stream.len() {
+        let length = u16_at(stream, offset + 2) as usize;
+        let data = stream.get(offset + 4..offset + 4 + length).unwrap_or(&stream[offset + 4..]);
+        records.push((u16_at(stream, offset), data));
+        offset += 4 + length;
+    }
+
+    let mut biff8 = true;
+    let mut strings = vec![];
+    let mut text = String::new();
+    let mut last_row = None;
+    for (index, (kind, data)) in records.iter().enumerate() {
+        let cell = match *kind {
+            XLS_BOF => {
+                if index == 0 {
+                    biff8 = u16_at(data, 0) == BIFF8_VERSION;
+                } else if !text.is_empty() && !text.ends_with("\n\n") {
+                    // Blank line between the sheets
+                    text.push('\n');
+                }
+                last_row = None;
+                continue;
+            },
+            XLS_FILEPASS => return Err(legacy_error("encrypted workbook")),
+            XLS_SST => {
+                let mut segments = vec![*data];
+                segments.extend(records[index + 1..].iter().take_while(|(kind, _)| *kind == XLS_CONTINUE).map(|(_, data)| *data));
+                strings = read_sst(segments);
+                continue;
+            },
+            XLS_LABEL_SST => strings.get(u32_at(data, 6) as usize).cloned(),
+            XLS_LABEL if biff8 => data.get(6..).map(|string| {
+                let mut reader = BiffReader::new(vec![string]);
+                reader.string()
+            }),
+            XLS_LABEL => data.get(8..8 + u16_at(data, 6) as usize).map(decode_windows_1252),
+            _ => None,
+        };
+        let Some(cell) = cell else { continue };
+        let row = u16_at(data, 0);
+        match last_row {
+            Some(last) if last == row => text.push('\t'),
+            Some(_) => text.push('\n'),
+            None if !text.is_empty() => text.push('\n'),
+            None => (),
+        }
+        text.push_str(&cell);
+        last_row = Some(row);
+    }
+    Ok(text)
+}
+
+/// Shared strings table: count, unique count then the strings, that may continue in the next records
+fn read_sst(segments: Vec<&[u8]>) -> Vec<String> {
+    let mut reader = BiffReader::new(segments);
+    reader.u32();
+    let count = reader.u32() as usize;
+    let mut strings = Vec::with_capacity(count.min(65536));
+    while strings.len() < count && !reader.is_end() {
+        strings.push(reader.string());
+    }
+    strings
+}
+
+/// Reader of the data of a record and of its CONTINUE records
+struct BiffReader<'a> {
+    segments: Vec<&'a [u8]>,
+    segment: usize,
+    position: usize,
+}
+
+impl<'a> BiffReader<'a> {
+    fn new(segments: Vec<&'a [u8]>) -> Self {
+        BiffReader { segments, segment: 0, position: 0 }
+    }
+
+    fn is_end(&self) -> bool {
+        self.segment >= self.segments.len()
+            || (self.segment == self.segments.len() - 1 && self.position >= self.segments[self.segment].len())
+    }
+
+    fn is_segment_end(&self) -> bool {
+        self.segments.get(self.segment).is_some_and(|segment| self.position >= segment.len())
+    }
+
+    fn u8(&mut self) -> u8 {
+        while self.is_segment_end() {
+            self.segment += 1;
+            self.position = 0;
+        }
+        let byte = self.segments.get(self.segment).and_then(|segment| segment.get(self.position)).copied();
+        self.position += 1;
+        byte.unwrap_or_default()
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.u8(), self.u8()])
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.u8(), self.u8(), self.u8(), self.u8()])
+    }
+
+    /// Unicode string: length, flags (UTF-16 or 8 bits, rich text runs, phonetic data) and chars,
+    /// chars continued in the next record are preceded by new flags
+    fn string(&mut self) -> String {
+        let length = self.u16() as usize;
+        let flags = self.u8();
+        let mut wide = flags & 0x01 != 0;
+        let runs = if flags & 0x08 != 0 { self.u16() as usize } else { 0 };
+        let extra = if flags & 0x04 != 0 { self.u32() as usize } else { 0 };
+
+        let mut units = Vec::with_capacity(length);
+        while units.len() < length && !self.is_end() {
+            if self.is_segment_end() {
+                self.segment += 1;
+                self.position = 0;
+                wide = self.u8() & 0x01 != 0;
+                continue;
+            }
+            units.push(if wide { self.u16() } else { self.u8() as u16 });
+        }
+        self.skip(runs * 4 + extra);
+        String::from_utf16_lossy(&units)
+    }
+
+    /// Skip bytes across the records, up to the end of the data (sizes of corrupt files)
+    fn skip(&mut self, mut count: usize) {
+        while count > 0 && !self.is_end() {
+            if self.is_segment_end() {
+                self.segment += 1;
+                self.position = 0;
+                continue;
+            }
+            let skipped = (self.segments[self.segment].len() - self.position).min(count);
+            self.position += skipped;
+            count -= skipped;
+        }
+    }
+}
+
+fn ppt_text(stream: &[u8]) -> String {
+    let mut texts = vec![];
+    let mut offset = 0;
+    while offset + 8 <= stream.len() {
+        let version = u16_at(stream, offset) & 0x000f;
+        let kind = u16_at(stream, offset + 2);
+        let length = u32_at(stream, offset + 4) as usize;
+        offset += 8;
+        // Records of a container follow its header
+        if version == PPT_CONTAINER {
+            continue;
+        }
+        let data = stream.get(offset..offset.saturating_add(length)).unwrap_or_default();
+        match kind {
+            PPT_TEXT_CHARS => {
+                let units: Vec<u16> = (0..data.len() / 2).map(|i| u16_at(data, i * 2)).collect();
+                texts.push(String::from_utf16_lossy(&units));
+            },
+            // The bytes are the low bytes of UTF-16 chars
+            PPT_TEXT_BYTES => texts.push(data.iter().map(|b| *b as char).collect()),
+            _ => (),
+        }
+        offset = offset.saturating_add(length);
+    }
+    texts.join("\n").replace(['\r', '\x0b'], "\n")
+}
+
+fn legacy_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::cfb::test::compound_file;
+
+    fn record(kind: u16, data: &[u8]) -> Vec<u8> {
+        [&kind.to_le_bytes()[..], &(data.len() as u16).to_le_bytes(), data].concat()
+    }
+
+    #[test]
+    fn legacy_doc() {
+        // Two pieces: 8 bits "Hello \x13 PAGE \x14" and UTF-16 "1\x15 wörld\r"
+        let first = b"Hello \x13 PAGE \x14";
+        let second: Vec<u8> = "1\x15 wörld\r".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let mut word = vec![0u8; 0x400];
+        word[0..2].copy_from_slice(&WORD_IDENT.to_le_bytes());
+        word[2..4].copy_from_slice(&WORD_97_VERSION.to_le_bytes());
+        word[0x0a..0x0c].copy_from_slice(&FIB_TABLE_1.to_le_bytes());
+        word.extend(first);
+        let second_start = word.len() as u32;
+        word.extend(&second);
+
+        let cps = [0u32, first.len() as u32, (first.len() + second.len() / 2) as u32];
+        let mut plc: Vec<u8> = cps.iter().flat_map(|cp| cp.to_le_bytes()).collect();
+        for fc in [(0x400 * 2) | PIECE_COMPRESSED, second_start] {
+            plc.extend([0, 0]);
+            plc.extend(fc.to_le_bytes());
+            plc.extend([0, 0]);
+        }
+        let mut table = vec![0x01, 0x02, 0x00, 0xaa, 0xbb, 0x02];
+        table.extend((plc.len() as u32).to_le_bytes());
+        table.extend(plc);
+        word[FIB_FC_CLX..FIB_FC_CLX + 4].copy_from_slice(&0u32.to_le_bytes());
+
+        let cfb = CompoundFile::new(compound_file(&[("WordDocument", word), ("1Table", table)])).unwrap();
+        assert_eq!("Hello 1 wörld\n", doc_text(&cfb).unwrap());
+    }
+
+    #[test]
+    fn legacy_xls() {
+        // Shared strings, the second one continued in a CONTINUE record as UTF-16
+        let mut sst = vec![];
+        sst.extend(3u32.to_le_bytes());
+        sst.extend(2u32.to_le_bytes());
+        sst.extend([4, 0, 0]);
+        sst.extend(b"Name");
+        sst.extend([4, 0, 0]);
+        sst.extend(b"Ca");
+        let mut next = vec![1];
+        next.extend("fé".encode_utf16().flat_map(|c| c.to_le_bytes()));
+        let label_sst = |row: u16, col: u16, index: u32| [&row.to_le_bytes()[..], &col.to_le_bytes(), &[0, 0], &index.to_le_bytes()].concat();
+
+        let stream = [
+            record(XLS_BOF, &BIFF8_VERSION.to_le_bytes()),
+            record(XLS_SST, &sst),
+            record(XLS_CONTINUE, &next),
+            record(XLS_BOF, &BIFF8_VERSION.to_le_bytes()),
+            record(XLS_LABEL_SST, &label_sst(0, 0, 0)),
+            record(XLS_LABEL_SST, &label_sst(0, 1, 1)),
+            record(XLS_LABEL, &[&1u16.to_le_bytes()[..], &[0, 0, 0, 0], &[2, 0, 0], b"Hi"].concat()),
+        ].concat();
+        assert_eq!("Name\tCafé\nHi", xls_text(&stream).unwrap());
+
+        // Phonetic data size past the end of the records
+        let corrupt = [&2u32.to_le_bytes()[..], &2u32.to_le_bytes(), &[1, 0, 0x04], &u32::MAX.to_le_bytes(), b"A"].concat();
+        assert_eq!(vec!["A"], read_sst(vec![&corrupt, &[0, 1, 2]]));
+    }
+
+    #[test]
+    fn legacy_ppt() {
+        let atom = |kind: u16, data: &[u8]| [&0u16.to_le_bytes()[..], &kind.to_le_bytes(), &(data.len() as u32).to_le_bytes(), data].concat();
+        let title: Vec<u8> = "Plan\rQ1".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        let atoms = [atom(PPT_TEXT_CHARS, &title), atom(0x0fba, b"x\0"), atom(PPT_TEXT_BYTES, b"Notes")].concat();
+        let container = [&0x000fu16.to_le_bytes()[..], &0x03e8u16.to_le_bytes(), &(atoms.len() as u32).to_le_bytes(), &atoms].concat();
+        assert_eq!("Plan\nQ1\nNotes", ppt_text(&container));
+    }
+}
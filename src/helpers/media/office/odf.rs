@@ -0,0 +1,208 @@
+use std::{fs::File, io::{self, BufReader, Read}};
+use quick_xml::{events::{BytesStart, Event}, Reader};
+use zip::ZipArchive;
+
+type Result<T> = std::result::Result<T, std::io::Error>;
+
+/// Dublin Core metadata of an OpenDocument file (meta.xml)
+#[derive(Debug, Default)]
+pub struct OdfMetadata {
+    pub title: String,
+    pub subject: String,
+    pub description: String,
+    pub creator: String,
+    pub keywords: Vec<String>,
+}
+
+/// Read the text of an OpenDocument file (odt, ods, odp) from its content.xml
+///
+/// Paragraphs and headings are separated by a new line,
+/// spreadsheet cells by a tab and rows by a new line
+pub fn read_content(file_path: &str) -> Result<String> {
+    let xml = read_zip_entry(file_path, "content.xml")?;
+    content_from_xml(&xml)
+}
+
+/// Read the title, subject, description, creator and keywords from meta.xml
+pub fn read_metadata(file_path: &str) -> Result<OdfMetadata> {
+    let xml = read_zip_entry(file_path, "meta.xml")?;
+    metadata_from_xml(&xml)
+}
+
+fn read_zip_entry(file_path: &str, entry_name: &str) -> Result<String> {
+    let file = File::open(file_path)?;
+    let mut archive = ZipArchive::new(BufReader::new(file))?;
+    let mut entry = archive.by_name(entry_name)?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml)?;
+    Ok(xml)
+}
+
+fn content_from_xml(xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut content = String::new();
+    // Text of the current spreadsheet row, None outside of a table
+    let mut row: Option<Vec<String>> = None;
+    let mut cell = String::new();
+    // Depth inside elements whose text is not part of the document (deleted changes...)
+    let mut skip_depth = 0;
+    // Text outside paragraphs is only xml indentation
+    let mut paragraph_depth = 0;
+
+    loop {
+        let event = reader.read_event().map_err(xml_error)?;
+        match event {
+            Event::Start(ref e) if skip_depth > 0 || is_skipped(e) => skip_depth += 1,
+            Event::End(_) if skip_depth > 0 => skip_depth -= 1,
+            Event::Start(ref e) => match e.name().as_ref() {
+                b"text:p" | b"text:h" => paragraph_depth += 1,
+                b"table:table-row" => row = Some(vec![]),
+                _ => (),
+            },
+            Event::Empty(ref e) if skip_depth == 0 => {
+                let text = match e.name().as_ref() {
+                    b"text:s" => " ".repeat(space_count(e)),
+                    b"text:tab" => "\t".to_string(),
+                    b"text:line-break" => "\n".to_string(),
+                    b"text:p" | b"text:h" => "\n".to_string(),
+                    b"table:table-cell" if row.is_some() => {
+                        row.as_mut().unwrap().push(String::new());
+                        continue;
+                    },
+                    _ => continue,
+                };
+                push_text(&mut content, &mut cell, row.is_some(), &text);
+            },
+            Event::Text(ref e) if skip_depth == 0 && paragraph_depth > 0 => {
+                let text = e.unescape().map_err(xml_error)?;
+                push_text(&mut content, &mut cell, row.is_some(), &collapse_whitespace(&text));
+            },
+            Event::End(ref e) => match e.name().as_ref() {
+                b"text:p" | b"text:h" => {
+                    paragraph_depth -= 1;
+                    push_text(&mut content, &mut cell, row.is_some(), "\n");
+                },
+                b"table:table-cell" | b"table:covered-table-cell" => if let Some(row) = row.as_mut() {
+                    row.push(cell.trim().replace('\n', " "));
+                    cell.clear();
+                },
+                b"table:table-row" => if let Some(row) = row.take() {
+                    let line = row.join("\t");
+                    let line = line.trim_end();
+                    if !line.is_empty() {
+                        content.push_str(line);
+                        content.push('\n');
+                    }
+                },
+                b"draw:page" => content.push('\n'),
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(content)
+}
+
+fn metadata_from_xml(xml: &str) -> Result<OdfMetadata> {
+    let mut reader = Reader::from_str(xml);
+    let mut metadata = OdfMetadata::default();
+    let mut current = vec![];
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => current = e.name().as_ref().to_vec(),
+            Event::End(_) => current.clear(),
+            Event::Text(e) => {
+                let text = e.unescape().map_err(xml_error)?.trim().to_string();
+                match current.as_slice() {
+                    b"dc:title" => metadata.title = text,
+                    b"dc:subject" => metadata.subject = text,
+                    b"dc:description" => metadata.description = text,
+                    b"dc:creator" => metadata.creator = text,
+                    b"meta:initial-creator" if metadata.creator.is_empty() => metadata.creator = text,
+                    b"meta:keyword" => metadata.keywords.push(text),
+                    _ => (),
+                }
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(metadata)
+}
+
+/// Text of a cell is kept until the end of the cell, paragraphs of a cell are on the same line
+fn push_text(content: &mut String, cell: &mut String, in_row: bool, text: &str) {
+    if in_row {
+        cell.push_str(text);
+    } else {
+        content.push_str(text);
+    }
+}
+
+/// Spaces, tabs and new lines in a paragraph are displayed as one space (text:s, text:tab are used instead)
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut previous_space = false;
+    for c in text.chars() {
+        let is_space = matches!(c, ' ' | '\t' | '\n' | '\r');
+        if !(is_space && previous_space) {
+            collapsed.push(if is_space { ' ' } else { c });
+        }
+        previous_space = is_space;
+    }
+    collapsed
+}
+
+/// Elements without document text: deleted text of tracked changes, annotations
+fn is_skipped(e: &BytesStart) -> bool {
+    matches!(e.name().as_ref(), b"text:tracked-changes" | b"office:annotation" | b"office:forms")
+}
+
+/// <text:s text:c="3"/> is 3 spaces
+fn space_count(e: &BytesStart) -> usize {
+    match e.try_get_attribute("text:c") {
+        Ok(Some(attr)) => String::from_utf8_lossy(&attr.value).parse::<usize>().unwrap_or(1),
+        _ => 1,
+    }
+}
+
+fn xml_error<E: std::fmt::Display>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid OpenDocument xml: {err}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn odf_text_content() {
+        let xml = r#"<office:document-content><office:body><office:text>
+<text:h>Chapter&#160;1</text:h><text:p>Un <text:span>été</text:span><text:s text:c="2"/>à Paris<text:line-break/>fin</text:p>
+<text:tracked-changes><text:p>deleted</text:p></text:tracked-changes>
+</office:text></office:body></office:document-content>"#;
+        let content = content_from_xml(xml).unwrap();
+        assert_eq!("Chapter\u{a0}1\nUn été  à Paris\nfin\n", content);
+    }
+
+    #[test]
+    fn odf_spreadsheet_content() {
+        let xml = r#"<office:spreadsheet><table:table>
+<table:table-row><table:table-cell><text:p>Name</text:p></table:table-cell><table:table-cell/><table:table-cell><text:p>Total &amp; tax</text:p></table:table-cell></table:table-row>
+<table:table-row><table:table-cell/></table:table-row>
+</table:table></office:spreadsheet>"#;
+        let content = content_from_xml(xml).unwrap();
+        assert!(content.contains("Name\t\tTotal & tax\n"));
+        assert!(!content.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn odf_metadata() {
+        let xml = r#"<office:meta><meta:initial-creator>Jane</meta:initial-creator><dc:title>Report</dc:title><meta:keyword>a</meta:keyword><meta:keyword>b</meta:keyword></office:meta>"#;
+        let metadata = metadata_from_xml(xml).unwrap();
+        assert_eq!("Report", metadata.title);
+        assert_eq!("Jane", metadata.creator);
+        assert_eq!(vec!["a", "b"], metadata.keywords);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod content;
 pub mod result;
 pub mod metadata;
+pub mod ocr;
 mod provider;
 
 use std::{io, fs};
@@ -8,11 +9,16 @@ use sha256::digest;
 
 use crate::helpers::{file, rtrim_char, ltrim_char, command, media::pdf::result::PdfResult};
 
-use self::provider::{exif, pdfprov, local};
+use self::{content::PdfContent, provider::{exif, pdfprov, local}};
 
 use super::normalize_media_title;
 
-pub fn get_pdf_result(base_path: &String, file_path: &String) -> Result<PdfResult, io::Error> {
+/// Separator of the pages in the content, as pdftotext does
+pub const PAGE_SEPARATOR: char = '\x0c';
+
+/// Metadata of a pdf, and the text of its pages if `with_content` (search, catalog):
+/// the scanned pages are read by OCR, too slow to run for the metadata only
+pub fn get_pdf_result(base_path: &String, file_path: &String, with_content: bool) -> Result<PdfResult, io::Error> {
     let file_size: usize = file::file_size(file_path).unwrap_or_default() as usize;
     let relative_file_path = file_path.replace(base_path, "");
 
@@ -28,12 +34,18 @@ pub fn get_pdf_result(base_path: &String, file_path: &String) -> Result<PdfResul
         local::from_local(file_path).unwrap()
     };
 
+    let content = match with_content {
+        true => get_pdf_pages(file_path, &hash).join(&PAGE_SEPARATOR.to_string()),
+        false => String::new(),
+    };
+
     return Ok(PdfResult {
         title: normalize_media_title(&metadata.title),
         summary: metadata.summary,
         casts: metadata.casts,
         genres: metadata.genres,
         year: metadata.year,
+        content,
 
         provider: String::from("local"),
 
@@ -48,6 +60,26 @@ pub fn get_pdf_result(base_path: &String, file_path: &String) -> Result<PdfResul
     });    
 }
 
+/// Text of each page, pages without text layer (scanned) are read by OCR
+pub fn get_pdf_pages(file_path: &str, hash: &str) -> Vec<String> {
+    // pdftotext is slower?
+    // pages are separated by form feed
+    let content = command::exec("___pdftotext", ["-layout", file_path, "-"]);
+    let pages: Vec<String> = if !content.is_empty() {
+        content.split(PAGE_SEPARATOR).map(|p| p.to_string()).collect()
+    } else {
+        PdfContent::new(file_path).collect()
+    };
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| match text.trim().is_empty() {
+            true => ocr::ocr_page(file_path, hash, index + 1),
+            false => text,
+        })
+        .collect()
+}
+
 // TODO: generate thumb
 pub fn generate_thumb(src_path: &String, dest_path: &String, size: &str) -> Vec<u8> {
     let size = size.replace(":", "x");
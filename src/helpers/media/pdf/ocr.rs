@@ -0,0 +1,38 @@
+use std::{env, fs};
+use sha256::digest;
+use crate::helpers::{cache, command, media::image::ocr};
+
+/// Cache sub directory of the OCR text, one file per pdf hash and page
+const OCR_CACHE_DIR: &str = ".ocr";
+
+/// Resolution of the rasterized pages, tesseract works best around 300 dpi
+const OCR_RESOLUTION: &str = "300";
+
+/// Text of a page without text layer (scanned page):
+/// the page is rasterized with pdftoppm then read by tesseract
+///
+/// The text is cached by file hash and page (1 based)
+pub fn ocr_page(file_path: &str, hash: &str, page: usize) -> String {
+    let key = format!("{hash}-{page}");
+    if let Some((_, text)) = cache::get_cache(&key, OCR_CACHE_DIR) {
+        return text;
+    }
+
+    // pdftoppm adds the extension to the output prefix
+    let prefix = env::temp_dir().join(format!("oms-ocr-{}", digest(&key))).display().to_string();
+    let page = page.to_string();
+    command::exec(
+        "pdftoppm",
+        ["-f", &page, "-l", &page, "-r", OCR_RESOLUTION, "-png", "-singlefile", file_path, &prefix]
+    );
+    let image_path = format!("{prefix}.png");
+    if fs::metadata(&image_path).is_err() {
+        return String::new();
+    }
+    let text = ocr(&image_path);
+    let _ = fs::remove_file(&image_path);
+
+    // Pages without text are cached too, they are not rasterized again
+    cache::write_cache_string(&key, &text, OCR_CACHE_DIR);
+    text
+}
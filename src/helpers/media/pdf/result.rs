@@ -3,8 +3,6 @@ use core::fmt;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
-use crate::helpers::string;
-
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PdfResult {
@@ -14,6 +12,9 @@ pub struct PdfResult {
     pub year: u16,  
     pub genres: Vec<String>, 
     pub casts: Vec<String>, 
+    /// Text of the pages, separated by form feed (empty when not read)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub content: String,
 
     pub provider: String,
 
@@ -44,27 +45,15 @@ impl fmt::Display for PdfResult {
 }
 
 impl PdfResult {
-    pub fn search(&self, term: &String) -> Vec<(&str, String)> {
-        let mut result = vec![];
-        if string::text_contains(&self.full_path, term) {
-            result.push(("File", self.full_path.to_string()));
-        }
-        if string::text_contains(&self.title, term) {
-            result.push(("Title", self.title.to_string()));
-        }
-        if string::text_contains(&self.summary, term) {
-            result.push(("Subject", self.summary.to_string()));
-        }
-        if string::text_contains(&self.summary, term) {
-            result.push(("Summary", self.summary.to_string()));
-        }
-        if string::text_contains(&self.casts.join(", "), term) {
-            result.push(("Authors", self.casts.join(", ")));
-        }        
-        if string::text_contains(&self.genres.join(", "), term) {
-            result.push(("Genres", self.genres.join(", ")));
-        }
-
-        return result;
+    /// Searchable fields, labelled by name
+    pub fn fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("File", self.full_path.to_string()),
+            ("Title", self.title.to_string()),
+            ("Subject", self.summary.to_string()),
+            ("Summary", self.summary.to_string()),
+            ("Authors", self.casts.join(", ")),
+            ("Genres", self.genres.join(", ")),
+        ]
     }
 }
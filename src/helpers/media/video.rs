@@ -1,5 +1,6 @@
 pub mod metadata;
 pub mod result;
+pub mod subtitle;
 pub mod title;
 
 use std::{fs, io, path::Path};
@@ -4,7 +4,7 @@ use std::io;
 use colored::Colorize;
 use sha256::digest;
 
-use crate::helpers::{self, string::text_contains, file, cache};
+use crate::helpers::{self, file, cache};
 
 use super::{video_duration, title::VideoTitle, provider::{tmdb::TMDb, omdb::OMDb, local::{Local, LocalParam}}};
 
@@ -48,21 +48,14 @@ impl fmt::Display for VideoResult {
 }
 
 impl VideoResult {
-    pub fn search(&self, term: &String) -> Vec<(&str, String)> {
-        let mut result = vec![];
-        if text_contains(&self.title, term) {
-            result.push(("Title", self.title.to_string()));
-        }
-        if text_contains(&self.summary, term) {
-            result.push(("Summary", self.summary.to_string()));
-        }
-        if text_contains(&self.genres.join(", "), term) {
-            result.push(("Genres", self.genres.join(", ")));
-        }
-        if text_contains(&self.casts.join(", "), term) {
-            result.push(("Casts", self.casts.join(", ")));
-        }
-        return result;
+    /// Searchable fields, labelled by name
+    pub fn fields(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Title", self.title.to_string()),
+            ("Summary", self.summary.to_string()),
+            ("Genres", self.genres.join(", ")),
+            ("Casts", self.casts.join(", ")),
+        ]
     }
 }
 
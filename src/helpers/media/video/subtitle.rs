@@ -0,0 +1,207 @@
+use std::path::Path;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::helpers::{cache, command, file};
+
+pub static SUBTITLE_EXTENSIONS: [&str; 4] = ["srt", "vtt", "ass", "ssa"];
+/// Subtitle codecs ffmpeg can convert to srt (bitmap subtitles are ignored)
+static TEXT_CODECS: [&str; 6] = ["subrip", "srt", "ass", "ssa", "webvtt", "mov_text"];
+
+static TAGS: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>|\{[^}]*\}").unwrap());
+
+/// A dialogue line and when it is displayed
+#[derive(Debug, PartialEq)]
+pub struct Cue {
+    /// Start time in milliseconds
+    pub start: u64,
+    pub text: String,
+}
+
+pub struct Subtitle {
+    /// Sidecar file name, or embedded stream (stream 2 eng)
+    pub source: String,
+    pub cues: Vec<Cue>,
+}
+
+/// Subtitles of a video: sidecar files (video.srt, video.en.vtt, video.ass...) then embedded text streams
+pub fn get_subtitles(video_path: &String) -> Vec<Subtitle> {
+    let mut subtitles = vec![];
+    for subtitle_path in sidecar_files(video_path) {
+        if let Ok(content) = std::fs::read(&subtitle_path) {
+            let content = String::from_utf8_lossy(&content);
+            subtitles.push(Subtitle {
+                source: file::get_file_name(&subtitle_path),
+                cues: parse_subtitle(&content, &file::get_extension(&subtitle_path).to_lowercase()),
+            });
+        }
+    }
+    for (index, language) in embedded_streams(video_path) {
+        let content = extract_stream(video_path, index);
+        subtitles.push(Subtitle {
+            source: format!("stream {index} {language}").trim().to_string(),
+            cues: parse_subtitle(&content, "srt"),
+        });
+    }
+    subtitles
+}
+
+/// Parse srt, vtt, ass or ssa content
+pub fn parse_subtitle(content: &str, format: &str) -> Vec<Cue> {
+    let content = content.trim_start_matches('\u{feff}');
+    match format {
+        "ass" | "ssa" => parse_ass(content),
+        _ => parse_timed_blocks(content),
+    }
+}
+
+/// 01:02:03.500
+pub fn format_timestamp(milliseconds: u64) -> String {
+    let seconds = milliseconds / 1000;
+    format!("{:02}:{:02}:{:02}.{:03}", seconds / 3600, seconds / 60 % 60, seconds % 60, milliseconds % 1000)
+}
+
+/// Files next to the video, with the same name and a subtitle extension
+fn sidecar_files(video_path: &String) -> Vec<String> {
+    let video_name = file::remove_extension(&file::get_file_name(video_path));
+    let dir = match Path::new(video_path).parent() {
+        Some(dir) => dir.to_path_buf(),
+        None => return vec![],
+    };
+    let mut files: Vec<String> = file::scan_files(&dir)
+        .into_iter()
+        .filter(|f| {
+            let name = file::get_file_name(f);
+            let extension = file::get_extension(&name).to_lowercase();
+            SUBTITLE_EXTENSIONS.contains(&extension.as_str())
+                && (file::remove_extension(&name) == video_name || name.starts_with(&format!("{video_name}.")))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Index and language of the text subtitle streams (ffprobe)
+fn embedded_streams(video_path: &str) -> Vec<(usize, String)> {
+    let output = command::exec(
+        "ffprobe",
+        ["-v", "error", "-select_streams", "s", "-show_entries", "stream=index,codec_name:stream_tags=language", "-of", "csv=p=0", video_path]
+    );
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let index = fields.next()?.trim().parse::<usize>().ok()?;
+            let codec = fields.next()?.trim();
+            let language = fields.next().unwrap_or_default().trim().to_string();
+            TEXT_CODECS.contains(&codec).then_some((index, language))
+        })
+        .collect()
+}
+
+/// Convert the stream to srt with ffmpeg, cached by file modification time
+fn extract_stream(video_path: &String, index: usize) -> String {
+    let cache_key = format!("{video_path}:{}:{index}", file::get_creation_time(video_path));
+    if let Some((_, content)) = cache::get_cache(&cache_key, ".subtitle") {
+        return content;
+    }
+    let content = command::exec(
+        "ffmpeg",
+        ["-v", "error", "-i", video_path, "-map", &format!("0:{index}"), "-f", "srt", "-"]
+    );
+    cache::write_cache_string(&cache_key, &content, ".subtitle");
+    content
+}
+
+/// srt and vtt: a "start --> end" line followed by the text lines, until an empty line
+fn parse_timed_blocks(content: &str) -> Vec<Cue> {
+    let mut cues = vec![];
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let Some((start, _)) = line.split_once("-->") else {
+            continue;
+        };
+        let Some(start) = parse_timestamp(start.trim()) else {
+            continue;
+        };
+        let text: Vec<String> = lines
+            .by_ref()
+            .take_while(|l| !l.trim().is_empty())
+            .map(clean_text)
+            .collect();
+        cues.push(Cue {
+            start,
+            text: text.join(" "),
+        });
+    }
+    cues
+}
+
+/// ass and ssa: "Dialogue:" lines of the [Events] section, fields described by its "Format:" line
+fn parse_ass(content: &str) -> Vec<Cue> {
+    let mut cues = vec![];
+    let mut format: Vec<String> = vec![];
+    for line in content.lines() {
+        if let Some(fields) = line.strip_prefix("Format:") {
+            format = fields.split(',').map(|f| f.trim().to_lowercase()).collect();
+        } else if let Some(fields) = line.strip_prefix("Dialogue:") {
+            let start_index = format.iter().position(|f| f == "start").unwrap_or(1);
+            let text_index = format.iter().position(|f| f == "text").unwrap_or(9);
+            // Text is the last field, it may contain commas
+            let fields: Vec<&str> = fields.splitn(text_index + 1, ',').collect();
+            if let (Some(start), Some(text)) = (fields.get(start_index), fields.get(text_index)) {
+                if let Some(start) = parse_timestamp(start.trim()) {
+                    cues.push(Cue {
+                        start,
+                        text: clean_text(&text.replace("\\N", " ").replace("\\n", " ")),
+                    });
+                }
+            }
+        }
+    }
+    cues
+}
+
+/// 01:02:03,500 (srt), 02:03.500 (vtt), 0:02:03.50 (ass) in milliseconds
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.split_whitespace().next()?;
+    let (time, fraction) = timestamp.split_once([',', '.']).unwrap_or((timestamp, "0"));
+    let mut seconds = 0;
+    for part in time.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    let milliseconds = format!("0.{fraction}").parse::<f64>().ok()? * 1000.;
+    Some(seconds * 1000 + milliseconds.round() as u64)
+}
+
+/// Remove html tags (<i>) and ass override tags ({\an8})
+fn clean_text(text: &str) -> String {
+    TAGS.replace_all(text, "").trim().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn subtitle_srt_vtt() {
+        let srt = "\u{feff}1\r\n00:01:02,500 --> 00:01:04,000\r\n<i>Hello</i>\r\nworld\r\n\r\n2\r\n01:00:00,000 --> 01:00:01,000\r\nBye\r\n";
+        assert_eq!(vec![
+            Cue { start: 62500, text: "Hello world".to_string() },
+            Cue { start: 3600000, text: "Bye".to_string() },
+        ], parse_subtitle(srt, "srt"));
+
+        let vtt = "WEBVTT\n\nNOTE a comment\n\nintro\n02:03.250 --> 02:04.000 align:start\nFirst line\n";
+        assert_eq!(vec![Cue { start: 123250, text: "First line".to_string() }], parse_subtitle(vtt, "vtt"));
+    }
+
+    #[test]
+    fn subtitle_ass() {
+        let ass = "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:05.50,0:00:07.00,Default,,0,0,0,,{\\an8}Well, hello\\Nthere\n";
+        assert_eq!(vec![Cue { start: 5500, text: "Well, hello there".to_string() }], parse_subtitle(ass, "ass"));
+    }
+
+    #[test]
+    fn subtitle_format_timestamp() {
+        assert_eq!("01:02:03.045", format_timestamp(3723045));
+    }
+}
@@ -25,6 +25,27 @@ pub fn colorize(line: &str, regex: &Regex, color: (u8, u8, u8)) -> Result<String
     Ok(cline)
 }
 
+/// Same as colorize, with the byte ranges to highlight
+pub fn colorize_ranges(line: &str, ranges: &[(usize, usize)], color: (u8, u8, u8)) -> Result<String, io::Error> {
+    let mut cline = String::new();
+    let mut last = 0;
+    for (start, end) in ranges {
+        match (line.get(last..*start), line.get(*start..*end)) {
+            (Some(before), Some(found)) => {
+                cline += before;
+                cline += format!("{}", found.on_truecolor(color.0, color.1, color.2)).as_str();
+                last = *end;
+            },
+            _ => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid range {start}..{end}")
+            )),
+        }
+    }
+    cline += &line[last..];
+    Ok(cline)
+}
+
 pub fn draw_image(thumb_path: &String, size: (u32, u32)) -> String {
     if let Err(_) = fs::metadata(thumb_path) {
         return String::new();
@@ -0,0 +1,543 @@
+use diacritics::remove_diacritics;
+use serde_json::{json, Value};
+
+/// Field prefixes allowed in a query, with the item labels they match
+/// (labels are the lowercase names of the fields and sections of the searched Document)
+static FIELDS: [(&str, &[&str]); 14] = [
+    ("title", &["title"]),
+    ("cast", &["casts", "authors", "artists"]),
+    ("author", &["authors", "casts"]),
+    ("artist", &["artists"]),
+    ("album", &["album"]),
+    ("genre", &["genres"]),
+    ("summary", &["summary", "subject"]),
+    ("content", &["content"]),
+    ("page", &["page"]),
+    ("chapter", &["chapter"]),
+    ("message", &["message"]),
+    ("subtitle", &["subtitle"]),
+    ("line", &["line"]),
+    ("file", &["file"]),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    /// Normalized text (lowercase, without diacritics)
+    text: String,
+    /// Text as written in the query, for the search engines doing their own normalization
+    original: String,
+    /// Field prefix (title, cast...), None to search in all fields
+    field: Option<String>,
+    negated: bool,
+}
+
+/// Boolean search query
+///
+/// Terms are separated by spaces and implicitly joined by AND,
+/// OR has lower precedence than AND.
+///
+/// * `foo AND bar`, `foo bar` - both terms must be found in the file
+/// * `foo OR bar` - one of the terms must be found
+/// * `-foo`, `NOT foo` - exclude files containing the term
+/// * `"exact phrase"` - search the phrase as is
+/// * `title:foo`, `cast:"john doe"`, `genre:`, `page:`, `chapter:`, `subtitle:`... - search only in this field
+///
+/// With fuzzy distance, terms also match text with a few typos (edit distance)
+///
+/// # Examples
+///
+/// ```
+/// use oms::helpers::query::Query;
+///
+/// let query = Query::from("\"hello world\" c++ -(2019)");
+/// let mut query_match = query.matcher();
+/// assert!(query_match.test("Line", "Hello World in C++"));
+/// assert!(query_match.is_match());
+///
+/// let mut query_match = query.matcher();
+/// query_match.test("Line", "Hello World in C++");
+/// query_match.test("Line", "Released in (2019)");
+/// assert!(!query_match.is_match());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// OR of AND clauses
+    clauses: Vec<Vec<Term>>,
+    /// Max edit distance allowed for a term, 0 for exact match
+    fuzzy: usize,
+}
+
+impl Query {
+    pub fn from(query: &str) -> Self {
+        let mut clauses: Vec<Vec<Term>> = vec![vec![]];
+        let mut negate_next = false;
+
+        for (word, quoted) in tokenize(query) {
+            if !quoted {
+                match word.as_str() {
+                    "OR" => {
+                        if clauses.last().is_some_and(|c| !c.is_empty()) {
+                            clauses.push(vec![]);
+                        }
+                        negate_next = false;
+                        continue;
+                    },
+                    "AND" => continue,
+                    "NOT" => {
+                        negate_next = !negate_next;
+                        continue;
+                    },
+                    _ => (),
+                }
+            }
+            if let Some(mut term) = parse_term(&word, quoted) {
+                term.negated ^= negate_next;
+                negate_next = false;
+                if let Some(clause) = clauses.last_mut() {
+                    clause.push(term);
+                }
+            }
+        }
+        clauses.retain(|c| !c.is_empty());
+        Query {
+            clauses,
+            fuzzy: 0,
+        }
+    }
+
+    pub fn set_fuzzy(&mut self, distance: usize) {
+        self.fuzzy = distance;
+    }
+
+    pub fn is_fuzzy(&self) -> bool {
+        self.fuzzy > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Start matching a new file (document) against this query
+    pub fn matcher(&self) -> QueryMatch<'_> {
+        QueryMatch {
+            query: self,
+            seen: self.clauses.iter().map(|c| vec![false; c.len()]).collect(),
+        }
+    }
+
+    /// Words a file must contain (as part of its words) to match each OR clause,
+    /// from the non-negated terms; an empty clause can match any file
+    pub fn required_words(&self) -> Vec<Vec<String>> {
+        self.clauses
+            .iter()
+            .map(|clause| clause
+                .iter()
+                .filter(|t| !t.negated)
+                .flat_map(|t| split_words(&t.text))
+                .collect())
+            .collect()
+    }
+
+    /// Elasticsearch bool query: a multi_match by term, in the given fields or in the field of its prefix
+    /// (file: is file_path), OR clauses as should, negated terms as must_not
+    pub fn elastic_query(&self, fields: &[&str]) -> Value {
+        let clauses: Vec<Value> = self.clauses
+            .iter()
+            .map(|clause| {
+                let (mut must, mut must_not) = (vec![], vec![]);
+                for term in clause {
+                    let term_fields: Vec<&str> = match &term.field {
+                        Some(field) => FIELDS
+                            .iter()
+                            .filter(|(name, _)| name == field)
+                            .flat_map(|(_, labels)| labels.iter().map(|label| if *label == "file" { "file_path" } else { label }))
+                            .collect(),
+                        None => fields.to_vec(),
+                    };
+                    let mut multi_match = json!({ "query": term.original, "fields": term_fields });
+                    if term.original.contains(char::is_whitespace) {
+                        multi_match["type"] = json!("phrase");
+                    } else if self.fuzzy > 0 {
+                        multi_match["fuzziness"] = json!(self.fuzzy.min(2));
+                    }
+                    if term.negated { &mut must_not } else { &mut must }.push(json!({ "multi_match": multi_match }));
+                }
+                json!({ "bool": { "must": must, "must_not": must_not } })
+            })
+            .collect();
+        json!({ "bool": { "should": clauses, "minimum_should_match": 1 } })
+    }
+
+    /// Byte ranges in text of all non-negated terms, regardless of the field
+    pub fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        let (normalized, offsets) = normalize(text);
+        let mut ranges = vec![];
+        for term in self.clauses.iter().flatten().filter(|t| !t.negated) {
+            ranges.extend(self.term_ranges(term, &normalized, &offsets).into_iter().map(|(s, e, _)| (s, e)));
+        }
+        merge_ranges(ranges)
+    }
+
+    /// Similarity of the best non-negated term found in text,
+    /// from 1 (exact match) to 0 (not found)
+    pub fn similarity(&self, text: &str) -> f32 {
+        let (normalized, offsets) = normalize(text);
+        self.clauses
+            .iter()
+            .flatten()
+            .filter(|t| !t.negated)
+            .flat_map(|t| self.term_ranges(t, &normalized, &offsets))
+            .map(|(_, _, score)| score)
+            .fold(0., f32::max)
+    }
+
+    /// Byte ranges (in the original text) and similarity of each occurrence of term
+    fn term_ranges(&self, term: &Term, normalized: &str, offsets: &[(usize, usize)]) -> Vec<(usize, usize, f32)> {
+        let term_len = term.text.chars().count();
+        // Short terms need to be more accurate
+        let distance = self.fuzzy.min(term_len / 3);
+        let found = if distance == 0 {
+            normalized
+                .match_indices(&term.text)
+                .map(|(start, m)| (start, start + m.len(), 0))
+                .collect()
+        } else {
+            fuzzy_find(&term.text, normalized, distance)
+        };
+        found
+            .into_iter()
+            .map(|(start, end, cost)| (
+                offsets[start].0,
+                offsets[end - 1].1,
+                1. - cost as f32 / term_len as f32,
+            ))
+            .collect()
+    }
+}
+
+/// State of a query over a whole file: remember which terms were found
+pub struct QueryMatch<'a> {
+    query: &'a Query,
+    seen: Vec<Vec<bool>>,
+}
+
+impl<'a> QueryMatch<'a> {
+    /// Check a piece of the file (a line, a page, a metadata...), labelled by field (Title, Page 3...)
+    /// Returns the byte ranges of non-negated terms found in text
+    pub fn find(&mut self, field: &str, text: &str) -> Vec<(usize, usize)> {
+        let field = field_label(field);
+        let (normalized, offsets) = normalize(text);
+        let mut ranges = vec![];
+
+        for (clause, seen) in self.query.clauses.iter().zip(self.seen.iter_mut()) {
+            for (term, seen) in clause.iter().zip(seen.iter_mut()) {
+                if !term_in_field(term, &field) {
+                    continue;
+                }
+                let term_ranges = self.query.term_ranges(term, &normalized, &offsets);
+                if term_ranges.is_empty() {
+                    continue;
+                }
+                *seen = true;
+                if !term.negated {
+                    ranges.extend(term_ranges.into_iter().map(|(s, e, _)| (s, e)));
+                }
+            }
+        }
+        merge_ranges(ranges)
+    }
+
+    /// Same as find, return true if text contains at least one non-negated term
+    pub fn test(&mut self, field: &str, text: &str) -> bool {
+        !self.find(field, text).is_empty()
+    }
+
+    /// Evaluate the query with all the pieces checked so far
+    pub fn is_match(&self) -> bool {
+        self.query.clauses.iter().zip(self.seen.iter()).any(|(clause, seen)| {
+            clause.iter().zip(seen.iter()).all(|(term, seen)| *seen != term.negated)
+        })
+    }
+
+    /// The file matches whatever its pieces not checked yet: a clause has all its terms found and none negated
+    pub fn is_certain_match(&self) -> bool {
+        self.query.clauses.iter().zip(self.seen.iter()).any(|(clause, seen)| {
+            clause.iter().zip(seen.iter()).all(|(term, seen)| *seen && !term.negated)
+        })
+    }
+}
+
+/// Split query into words, keep quoted phrases (and field:"quoted phrases") together
+fn tokenize(query: &str) -> Vec<(String, bool)> {
+    let mut tokens = vec![];
+    let mut chars = query.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut word = String::new();
+        let mut quoted = false;
+        let mut current = Some(c);
+        while let Some(c) = current {
+            if c.is_whitespace() {
+                break;
+            }
+            // Start of a phrase: "..." -"..." field:"..."
+            if c == '"' && (word.is_empty() || word == "-" || word.ends_with(':')) {
+                quoted = true;
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                }
+                break;
+            }
+            word.push(c);
+            current = chars.next();
+        }
+        if !word.is_empty() {
+            tokens.push((word, quoted));
+        }
+    }
+    tokens
+}
+
+fn parse_term(word: &str, quoted: bool) -> Option<Term> {
+    let mut word = word;
+    let mut negated = false;
+    if word.len() > 1 && word.starts_with('-') {
+        negated = true;
+        word = &word[1..];
+    }
+
+    let mut field = None;
+    if let Some((prefix, text)) = word.split_once(':') {
+        let prefix = prefix.to_lowercase();
+        if (!text.is_empty() || quoted) && FIELDS.iter().any(|(name, _)| *name == prefix) {
+            field = Some(prefix);
+            word = text;
+        }
+    }
+
+    let (text, _) = normalize(word);
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(Term {
+        text,
+        original: word.to_string(),
+        field,
+        negated,
+    })
+}
+
+/// Title -> title, Page 3 -> page, 12 -> line
+fn field_label(field: &str) -> String {
+    let label: String = field
+        .trim()
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect();
+    if label.is_empty() {
+        return String::from("line");
+    }
+    label.to_lowercase()
+}
+
+fn term_in_field(term: &Term, label: &str) -> bool {
+    match &term.field {
+        None => true,
+        Some(field) => FIELDS
+            .iter()
+            .filter(|(name, _)| name == field)
+            .any(|(_, labels)| labels.contains(&label)),
+    }
+}
+
+/// Approximate substring search (Sellers algorithm)
+/// Returns byte ranges in text with at most distance edits from pattern, and their edit distance
+fn fuzzy_find(pattern: &str, text: &str, distance: usize) -> Vec<(usize, usize, usize)> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<(usize, char)> = text.char_indices().collect();
+    let text_len = text.last().map_or(0, |(i, c)| i + c.len_utf8());
+    let m = pattern.len();
+
+    // Edit distance of pattern[..i] ending at the current text char, and where it starts
+    let mut cost: Vec<usize> = (0..=m).collect();
+    let mut start: Vec<usize> = vec![0; m + 1];
+
+    let mut found = vec![];
+    // Best (cost, start, end) of the current run of matching positions
+    let mut best: Option<(usize, usize, usize)> = None;
+
+    for (j, (_, c)) in text.iter().enumerate() {
+        let mut diagonal = (cost[0], start[0]);
+        cost[0] = 0;
+        start[0] = j + 1;
+        for i in 1..=m {
+            let left = (cost[i], start[i]);
+            let substitution = (diagonal.0 + usize::from(pattern[i - 1] != *c), diagonal.1);
+            let deletion = (cost[i - 1] + 1, start[i - 1]);
+            let insertion = (left.0 + 1, left.1);
+            let min = [deletion, insertion]
+                .into_iter()
+                .fold(substitution, |a, b| if b.0 < a.0 { b } else { a });
+            cost[i] = min.0;
+            start[i] = min.1;
+            diagonal = left;
+        }
+
+        if cost[m] <= distance {
+            let candidate = (cost[m], start[m], j + 1);
+            best = match best {
+                Some(b) if b.0 <= candidate.0 => Some(b),
+                _ => Some(candidate),
+            };
+        } else if let Some(b) = best.take() {
+            found.push(b);
+        }
+    }
+    found.extend(best);
+
+    let byte_index = |i: usize| text.get(i).map_or(text_len, |(b, _)| *b);
+    found
+        .into_iter()
+        .filter(|(_, s, e)| s < e)
+        .map(|(cost, s, e)| (byte_index(s), byte_index(e), cost))
+        .collect()
+}
+
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = vec![];
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Normalized words of text (lowercase, without diacritics), as compared to the query terms
+pub fn words(text: &str) -> Vec<String> {
+    split_words(&normalize(text).0)
+}
+
+fn split_words(normalized: &str) -> Vec<String> {
+    normalized
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
+
+/// Lowercase and remove diacritics,
+/// also returns for each byte of the result the byte range of the original char
+fn normalize(text: &str) -> (String, Vec<(usize, usize)>) {
+    let mut normalized = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        if c.is_ascii() {
+            normalized.push(c.to_ascii_lowercase());
+            offsets.push((index, end));
+        } else {
+            let folded = remove_diacritics(&c.to_lowercase().to_string()).to_lowercase();
+            for _ in 0..folded.len() {
+                offsets.push((index, end));
+            }
+            normalized.push_str(&folded);
+        }
+    }
+    (normalized, offsets)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_special_chars() {
+        let query = Query::from("c++ (2019)");
+        let mut query_match = query.matcher();
+        assert!(query_match.test("Line", "Learn C++"));
+        assert!(!query_match.is_match());
+        assert!(query_match.test("Line", "Released (2019)"));
+        assert!(query_match.is_match());
+    }
+
+    #[test]
+    fn query_or_not() {
+        let query = Query::from("foo OR bar NOT baz");
+        let mut query_match = query.matcher();
+        query_match.test("Line", "bar and baz");
+        assert!(!query_match.is_match());
+        query_match.test("Line", "foo");
+        assert!(query_match.is_match());
+    }
+
+    #[test]
+    fn query_field_prefix() {
+        let query = Query::from("cast:\"Tom Hanks\" genre:drama");
+        let mut query_match = query.matcher();
+        assert!(!query_match.test("Title", "Tom Hanks"));
+        assert!(query_match.test("Casts", "Tom Hanks, Robin Wright"));
+        assert!(!query_match.is_match());
+        assert!(query_match.test("Genres", "Drama, Romance"));
+        assert!(query_match.is_match());
+
+        let query = Query::from("page:tunique http://localhost");
+        let mut query_match = query.matcher();
+        assert!(query_match.test("Page 3", "La tunique"));
+        assert!(query_match.test("Line", "see http://localhost/oms"));
+        assert!(query_match.is_match());
+    }
+
+    #[test]
+    fn query_fuzzy() {
+        let mut query = Query::from("terminator");
+        let mut query_match = query.matcher();
+        assert!(!query_match.test("Title", "The Terminater"));
+
+        query.set_fuzzy(2);
+        let mut query_match = query.matcher();
+        assert_eq!(vec![(4, 14)], query_match.find("Title", "The Terminater"));
+        assert!(query_match.is_match());
+        assert_eq!(0.9, query.similarity("The Terminater"));
+        assert_eq!(1., query.similarity("Terminator 2"));
+        assert!(query.find("The Terminal").is_empty());
+    }
+
+    #[test]
+    fn query_ranges() {
+        let query = Query::from("ete");
+        assert_eq!(vec![(3, 8)], query.find("Un été"));
+        let mut query_match = query.matcher();
+        assert_eq!(vec![(0, 3), (5, 8)], query_match.find("Line", "ete, ete"));
+    }
+
+    #[test]
+    fn query_required_words() {
+        let query = Query::from("\"Hello, World\" -foo OR c++ OR -bar");
+        assert_eq!(vec![vec!["hello", "world"], vec!["c"], vec![]], query.required_words());
+        assert_eq!(vec!["un", "ete", "2019"], words("Un été (2019)"));
+    }
+
+    #[test]
+    fn query_elastic() {
+        let query = Query::from("Amélie cast:\"jean dupont\" -file:mkv OR dune");
+        assert_eq!(json!({ "bool": { "minimum_should_match": 1, "should": [
+            { "bool": {
+                "must": [
+                    { "multi_match": { "query": "Amélie", "fields": ["title", "summary"] } },
+                    { "multi_match": { "query": "jean dupont", "fields": ["casts", "authors", "artists"], "type": "phrase" } },
+                ],
+                "must_not": [{ "multi_match": { "query": "mkv", "fields": ["file_path"] } }],
+            } },
+            { "bool": { "must": [{ "multi_match": { "query": "dune", "fields": ["title", "summary"] } }], "must_not": [] } },
+        ] } }), query.elastic_query(&["title", "summary"]));
+    }
+}
@@ -11,6 +11,9 @@
 //!     * [x] .docx
 //!     * [x] .xlsx
 //!     * [x] .pptx
+//!     * [x] .doc, .xls, .ppt
+//!     * [x] email (.eml, .mbox)
+//!     * [x] html, xml, markdown, reStructuredText (text without the markup)
 //!     * [x] movie (need cache-path)
 //! * [ ] Read (output) content of file or an external source
 //!     * [x] text file
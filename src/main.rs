@@ -12,15 +12,18 @@ use std::process;
 /// cargo build --release
 /// cp target/release/oms ~/Documents/
 /// 
+/// Exit code: 0 on success, 1 on error, search exits with 1 when it found nothing and 2 on error
 fn main() {
-    App::from_env()
+    let app = App::from_env()
         .unwrap_or_else(|err| {
             eprintln!("\n{err}");
-            process::exit(1);
-        })
-        .run()
+            process::exit(App::args_exit_code());
+        });
+    app.run()
         .unwrap_or_else(|err| {
-            eprintln!("\n{err}\n");
-            process::exit(1);
+            if !App::is_no_match(err.as_ref()) {
+                eprintln!("\n{err}\n");
+            }
+            process::exit(app.exit_code(err.as_ref()));
         });
 }